@@ -0,0 +1,146 @@
+use crate::alphabet::MAX_LETTERS;
+use crate::dico::Dico;
+use crate::mask::{self, MaskError};
+use crate::random::Rng;
+use crate::session::Session;
+
+/// how well the solver plays when it's an opponent
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Strength {
+    /// any word still matching
+    Random,
+    /// the word still matching with the most common letters among the candidates,
+    /// like a human would play without computing the words left
+    Greedy,
+    /// one of the k best words of the solver
+    Top(usize),
+    /// the best word, like the solver
+    Optimal,
+}
+
+impl Strength {
+    /// word to play next, the last candidate is always played
+    pub fn pick(&self, session: &Session, dico: &Dico, rng: &mut Rng) -> Result<usize, MaskError> {
+        let candidates = session.candidates(&dico.codes)?;
+
+        match candidates.len() {
+            0 => return Err(MaskError::NoCandidate),
+            1 => return Ok(candidates[0]),
+            _ => (),
+        }
+
+        match self {
+            Strength::Random => Ok(candidates[rng.below(candidates.len())]),
+            Strength::Greedy => Ok(greedy(&dico.codes, &candidates)),
+            Strength::Top(k) => {
+                let scores = session.scores(&dico.codes)?;
+                let ranking: Vec<usize> = mask::rank(&scores)
+                    .into_iter()
+                    .filter(|&id| !scores[id].is_nan())
+                    .take(*k)
+                    .collect();
+
+                match ranking.len() {
+                    0 => Err(MaskError::NoCandidate),
+                    len => Ok(ranking[rng.below(len)]),
+                }
+            }
+            Strength::Optimal => mask::best(&session.scores(&dico.codes)?)
+                .map(|(id, _)| id)
+                .ok_or(MaskError::NoCandidate),
+        }
+    }
+}
+
+/// candidate whose distinct letters are found in the most candidates
+fn greedy(dico: &[Vec<u8>], candidates: &[usize]) -> usize {
+    let mut counts = [0_usize; MAX_LETTERS];
+
+    for &id in candidates {
+        let mut seen = 0_u32;
+
+        for &letter in &dico[id] {
+            if seen & (1 << letter) == 0 {
+                seen |= 1 << letter;
+                counts[letter as usize] += 1;
+            }
+        }
+    }
+
+    let value = |id: usize| {
+        let mut seen = 0_u32;
+
+        dico[id].iter().fold(0, |value, &letter| {
+            let new = seen & (1 << letter) == 0;
+            seen |= 1 << letter;
+            value + if new { counts[letter as usize] } else { 0 }
+        })
+    };
+
+    // the first one wins a tie, like mask::best
+    candidates.iter()
+        .copied()
+        .fold((candidates[0], 0), |(best, best_value), id| {
+            let value = value(id);
+            if value > best_value { (id, value) } else { (best, best_value) }
+        })
+        .0
+}
+
+impl std::fmt::Display for Strength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Strength::Random => write!(f, "random"),
+            Strength::Greedy => write!(f, "greedy"),
+            Strength::Top(k) => write!(f, "top{}", k),
+            Strength::Optimal => write!(f, "optimal"),
+        }
+    }
+}
+
+impl std::str::FromStr for Strength {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ERROR: &str = "strength must be random, greedy, top<k> (like top5) or optimal";
+
+        match s {
+            "random" => Ok(Strength::Random),
+            "greedy" => Ok(Strength::Greedy),
+            "optimal" => Ok(Strength::Optimal),
+            _ => match s.strip_prefix("top").map(str::parse) {
+                Some(Ok(k)) if k > 0 => Ok(Strength::Top(k)),
+                _ => Err(ERROR),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_strength() {
+        use super::Strength;
+
+        assert_eq!("top5".parse::<Strength>(), Ok(Strength::Top(5)));
+        assert_eq!(Strength::Top(5).to_string(), "top5");
+        assert_eq!("greedy".parse::<Strength>(), Ok(Strength::Greedy));
+        assert!("top0".parse::<Strength>().is_err());
+        assert!("strong".parse::<Strength>().is_err());
+    }
+
+    #[test]
+    fn greedy_word() {
+        use super::greedy;
+        use crate::alphabet::Alphabet;
+
+        let fr = Alphabet::default();
+        let dico: Vec<Vec<u8>> = ["carte", "tarte", "verte", "zozos"].iter()
+            .map(|word| fr.encode(word).unwrap())
+            .collect();
+
+        // 'a', 'r', 't' and 'e' are in most words, 'c' only in one
+        assert_eq!(greedy(&dico, &[0, 1, 2, 3]), 0);
+        assert_eq!(greedy(&dico, &[3, 1]), 1);
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::Path;
+
+/// a letter is a bit of `LetterMask`, so an alphabet can't have more letters
+pub const MAX_LETTERS: usize = 32;
+
+/// letters of a language, words are encoded as the index of their letters
+#[derive(Clone)]
+pub struct Alphabet {
+    lang: String,
+    /// a letter can be made of many chars, like the dutch "ij"
+    letters: Vec<String>,
+}
+
+impl Alphabet {
+    /// alphabet of the dictionaries in dir, read from `alphabet.txt` if any:
+    /// the language on the first row, then the letters separated by spaces
+    pub fn load(dir: &Path) -> Result<Self, AlphabetError> {
+        let content = match std::fs::read_to_string(dir.join("alphabet.txt")) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(_) => return Err(AlphabetError::ReadingFile),
+        };
+
+        let mut rows = content.lines();
+        let lang = rows.next().unwrap_or_default().trim().to_string();
+        let letters: Vec<String> = rows.flat_map(str::split_whitespace).map(str::to_string).collect();
+
+        if lang.is_empty() {
+            Err(AlphabetError::NoLang)
+        } else if letters.is_empty() || letters.len() > MAX_LETTERS {
+            Err(AlphabetError::InvalidSize(letters.len()))
+        } else if let Some(letter) = letters.iter().enumerate().find(|(i, l)| letters[..*i].contains(l)) {
+            Err(AlphabetError::Duplicate(letter.1.clone()))
+        } else {
+            Ok(Self { lang, letters })
+        }
+    }
+
+    pub fn lang(&self) -> &str {
+        &self.lang
+    }
+
+    /// count of letters
+    pub fn size(&self) -> usize {
+        self.letters.len()
+    }
+
+    pub fn letters(&self) -> impl Iterator<Item = &str> {
+        self.letters.iter().map(String::as_str)
+    }
+
+    pub fn letter(&self, index: u8) -> &str {
+        &self.letters[index as usize]
+    }
+
+    pub fn index(&self, letter: &str) -> Option<u8> {
+        self.letters.iter().position(|l| l == letter).map(|i| i as u8)
+    }
+
+    /// true if some letter starts with this char
+    pub fn contains(&self, c: char) -> bool {
+        self.letters.iter().any(|l| l.starts_with(c))
+    }
+
+    /// index and len in bytes of the longest letter at the start of s
+    pub fn next_letter(&self, s: &str) -> Option<(u8, usize)> {
+        self.letters.iter()
+            .enumerate()
+            .filter(|(_, l)| s.starts_with(l.as_str()))
+            .max_by_key(|(_, l)| l.len())
+            .map(|(i, l)| (i as u8, l.len()))
+    }
+
+    /// index of each letter of the word, or the first char without letter
+    pub fn encode(&self, word: &str) -> Result<Vec<u8>, char> {
+        let mut code = Vec::with_capacity(word.len());
+        let mut rest = word;
+
+        while let Some(c) = rest.chars().next() {
+            let (index, len) = self.next_letter(rest).ok_or(c)?;
+            code.push(index);
+            rest = &rest[len..];
+        }
+
+        Ok(code)
+    }
+}
+
+/// french alphabet, used when a directory doesn't provide one
+impl std::default::Default for Alphabet {
+    fn default() -> Self {
+        Self {
+            lang: "fr".to_string(),
+            letters: ('a'..='z').map(String::from).collect(),
+        }
+    }
+}
+
+pub enum AlphabetError {
+    ReadingFile,
+    NoLang,
+    InvalidSize(usize),
+    Duplicate(String),
+}
+
+impl std::fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlphabetError::ReadingFile => write!(f, "Error while reading alphabet.txt!"),
+            AlphabetError::NoLang => write!(f, "alphabet.txt must start with the language"),
+            AlphabetError::InvalidSize(size) => write!(f, "alphabet.txt has {} letters, it must have from 1 to {}", size, MAX_LETTERS),
+            AlphabetError::Duplicate(letter) => write!(f, "alphabet.txt has the letter '{}' twice", letter),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn encode_word() {
+        use super::Alphabet;
+
+        let fr = Alphabet::default();
+        assert_eq!(fr.encode("abz"), Ok(vec![0, 1, 25]));
+        assert_eq!(fr.encode("aéz"), Err('é'));
+
+        let nl = Alphabet {
+            lang: "nl".to_string(),
+            letters: ["a", "i", "ij", "s"].iter().map(|l| l.to_string()).collect(),
+        };
+
+        assert_eq!(nl.encode("ijsai"), Ok(vec![2, 3, 0, 1]));
+    }
+}
@@ -0,0 +1,196 @@
+use serde::Serialize;
+
+use crate::dico::Dico;
+use crate::hint::Hint;
+use crate::mask::{MaskError, ResultState};
+use crate::session::Session;
+
+/// a turn of a finished game, replayed with the answer known
+#[derive(Serialize)]
+pub struct Turn {
+    pub guess: String,
+    pub result: ResultState,
+    /// count of candidates before and after the guess
+    pub before: usize,
+    pub after: usize,
+    /// average count of words left by the guess, None if it's not in the dictionary
+    pub expected: Option<f32>,
+    /// recommended word of the solver with its score
+    pub best: Option<(String, f32)>,
+    /// words lost against the best word, 0 for the best play
+    pub skill: Option<f32>,
+    /// words removed beyond the average of the guess, positive when the answer was kind
+    pub luck: Option<f32>,
+}
+
+#[derive(Serialize)]
+pub struct Report {
+    pub answer: String,
+    /// rules and descriptor of the game, like the share text
+    pub game: String,
+    pub turns: Vec<Turn>,
+    pub found: bool,
+    pub summary: Option<String>,
+}
+
+/// replay the guesses against the answer, stopping once it's found
+pub fn replay(mut session: Session, dico: &Dico, answer: usize, guesses: &[String]) -> Result<Report, MaskError> {
+    let mut hint = Hint::default();
+    let mut turns = Vec::new();
+    let mut before = session.candidates(&dico.codes)?.len();
+
+    for guess in guesses {
+        if session.complet() {
+            break;
+        }
+
+        let code = session.alphabet().encode(guess).map_err(MaskError::InvalidChar)?;
+        let scores = session.scores(&dico.codes)?;
+        let grade = dico.position(guess).and_then(|id| hint.grade(&scores, id));
+
+        let mut result = ResultState::new(code.len(), false);
+        session.rules().feedback(&code, &dico.codes[answer], &mut result)?;
+        session.play(guess, result.clone())?;
+
+        let after = session.candidates(&dico.codes)?.len();
+
+        let expected = grade.as_ref().map(|grade| grade.score);
+
+        turns.push(Turn {
+            guess: guess.clone(),
+            result,
+            before,
+            after,
+            expected,
+            skill: grade.as_ref().map(|grade| grade.best_score - grade.score),
+            luck: expected.map(|expected| expected - after as f32),
+            best: grade.map(|grade| (dico.words[grade.best].clone(), grade.best_score)),
+        });
+
+        before = after;
+    }
+
+    Ok(Report {
+        answer: dico.words[answer].clone(),
+        game: format!("{} {}", session.rules(), session.desc()),
+        turns,
+        found: session.complet(),
+        summary: hint.summary().map(|summary| summary.to_string().trim_end().to_string()),
+    })
+}
+
+/// a number with 2 decimals, or '-' for guesses that can't be graded
+fn number(value: Option<f32>, sign: bool) -> String {
+    match value {
+        Some(value) if sign => format!("{:+.2}", value),
+        Some(value) => format!("{:.2}", value),
+        None => "-".to_string(),
+    }
+}
+
+impl Report {
+    fn conclusion(&self) -> String {
+        if self.found {
+            format!("Found in {} tries", self.turns.len())
+        } else {
+            format!("Not found after {} tries", self.turns.len())
+        }
+    }
+
+    /// same content as the text, in a table
+    pub fn markdown(&self) -> String {
+        let mut markdown = format!("# {} ({})\n\n", self.answer, self.game);
+        markdown += "| Turn | Guess | Result | Before | Best word | Expected | Left | Skill | Luck |\n";
+        markdown += "|---:|---|---|---:|---|---:|---:|---:|---:|\n";
+
+        for (i, turn) in self.turns.iter().enumerate() {
+            let best = match &turn.best {
+                Some((word, score)) => format!("{} ({:.2})", word, score),
+                None => "-".to_string(),
+            };
+
+            markdown += &format!(
+                "| {} | {} | `{}` | {} | {} | {} | {} | {} | {} |\n",
+                i + 1, turn.guess, turn.result, turn.before, best,
+                number(turn.expected, false), turn.after, number(turn.skill, true), number(turn.luck, true),
+            );
+        }
+
+        markdown += &format!("\n{}.", self.conclusion());
+
+        if let Some(summary) = &self.summary {
+            markdown += &format!(" {}.", summary);
+        }
+
+        markdown.push('\n');
+        markdown
+    }
+}
+
+/// one paragraph per turn, then the summary
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Analysis of {} ({})", self.answer, self.game)?;
+
+        for (i, turn) in self.turns.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(f, "Turn {}: {} {}", i + 1, turn.guess, turn.result)?;
+            writeln!(f, "  {} words before, {} left", turn.before, turn.after)?;
+
+            match (&turn.best, turn.expected) {
+                (Some((word, score)), Some(expected)) => {
+                    writeln!(f, "  best word: {} ({:.2}), guess: {:.2}", word, score, expected)?;
+                    writeln!(f, "  skill: {}, luck: {}", number(turn.skill, true), number(turn.luck, true))?;
+                }
+                _ => writeln!(f, "  not in the dictionary, not graded")?,
+            }
+        }
+
+        writeln!(f)?;
+        writeln!(f, "{}", self.conclusion())?;
+
+        if let Some(summary) = &self.summary {
+            writeln!(f, "{}", summary)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn replay_game() {
+        use super::replay;
+        use crate::alphabet::Alphabet;
+        use crate::dico::Dico;
+        use crate::rules::Rules;
+        use crate::session::Session;
+        use crate::MaskDescriptor;
+
+        let alphabet = Alphabet::default();
+        let words = ["carte", "crane", "tarte", "verte", "porte"];
+        let dico = Dico {
+            codes: words.iter().map(|word| alphabet.encode(word).unwrap()).collect(),
+            words: words.iter().map(|word| word.to_string()).collect(),
+            alphabet: alphabet.clone(),
+        };
+
+        let desc = MaskDescriptor { dico: None, len: 5, pattern: None };
+        let session = Session::new(desc, Rules::WORDLE, alphabet).unwrap_or_else(|err| panic!("{}", err));
+        let guesses = ["verte", "carte", "tarte"].map(String::from);
+        let report = replay(session, &dico, 0, &guesses).unwrap();
+
+        assert!(report.found);
+        assert_eq!(report.turns.len(), 2);
+        assert_eq!((report.turns[0].before, report.turns[0].after), (5, 3));
+        assert_eq!(report.turns[0].result.to_string(), "bbrrr");
+
+        // the skill and the luck add up to the words left
+        let turn = &report.turns[0];
+        let best = turn.best.as_ref().unwrap().1;
+        assert!((best - turn.skill.unwrap() - turn.luck.unwrap() - turn.after as f32).abs() < 1e-5);
+
+        assert!(report.markdown().contains("| 2 | carte | `rrrrr` | 3 |"));
+    }
+}
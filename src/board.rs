@@ -0,0 +1,110 @@
+use std::io::IsTerminal;
+
+use crate::mask::ResultColor;
+use crate::session::Session;
+
+/// colors are disabled when stdout isn't a terminal or NO_COLOR is set, see https://no-color.org
+pub fn enabled() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+/// every played word with the colors of its result, followed by the known letters
+/// without colors, the result is written after each word
+pub fn render(session: &Session, color: bool) -> String {
+    let mut board = String::new();
+    let positional = session.rules().positional();
+
+    for (word, result) in session.history() {
+        let letters = session.alphabet().encode(word).unwrap_or_default();
+
+        if !color {
+            for &letter in &letters {
+                board.push_str(&format!(" {} ", session.alphabet().letter(letter).to_uppercase()));
+            }
+
+            board.push_str(&format!("  {}\n", result));
+            continue;
+        }
+
+        if positional {
+            for (&letter, &color) in letters.iter().zip(result.colors()) {
+                board.push_str(&cell(session.alphabet().letter(letter), Some(color)));
+            }
+        } else {
+            // colors aren't at the place of the letters, they are shown after the word
+            for &letter in &letters {
+                board.push_str(&cell(session.alphabet().letter(letter), None));
+            }
+
+            board.push_str("  ");
+
+            for &color in result.colors() {
+                board.push_str(&cell(" ", Some(color)));
+            }
+        }
+
+        board.push('\n');
+    }
+
+    // \x1b[2m is dim
+    if color {
+        board.push_str("\x1b[2m");
+    }
+
+    for letter in session.known() {
+        board.push_str(&format!(" {} ", letter.unwrap_or(".").to_uppercase()));
+    }
+
+    if color {
+        board.push_str("\x1b[0m");
+    }
+
+    board.push('\n');
+    board
+}
+
+/// letter in bold white on the background of the color
+fn cell(letter: &str, color: Option<ResultColor>) -> String {
+    let background = match color {
+        Some(ResultColor::Red) => 41,
+        Some(ResultColor::Yellow) => 43,
+        Some(ResultColor::Blue) => 44,
+        None => 49,
+    };
+
+    format!("\x1b[1;97;{}m {} \x1b[0m", background, letter.to_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    fn session() -> crate::session::Session {
+        use crate::alphabet::Alphabet;
+        use crate::rules::Rules;
+        use crate::session::Session;
+
+        let mut session = Session::new("b5".parse().unwrap(), Rules::MOTUS, Alphabet::default())
+            .unwrap_or_else(|err| panic!("{}", err));
+        session.play("barres", "rrbbrr".try_into().unwrap_or_else(|err: crate::mask::ConvertError| panic!("{}", err))).unwrap();
+        session
+    }
+
+    #[test]
+    fn plain_board() {
+        use super::render;
+
+        let board = render(&session(), false);
+        assert_eq!(board, " B  A  R  R  E  S   rrbbrr\n B  A  .  .  E  S \n");
+    }
+
+    #[test]
+    fn colored_board() {
+        use super::render;
+
+        let board = render(&session(), true);
+        let rows: Vec<&str> = board.lines().collect();
+
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].starts_with("\x1b[1;97;41m B \x1b[0m\x1b[1;97;41m A \x1b[0m\x1b[1;97;44m R \x1b[0m"));
+        assert_eq!(rows[1], "\x1b[2m B  A  .  .  E  S \x1b[0m");
+    }
+}
@@ -0,0 +1,159 @@
+use std::path::Path;
+
+use crate::board;
+use crate::mask::{MaskError, ResultState};
+use crate::play;
+use crate::random::Rng;
+use crate::rules::Rules;
+use crate::session::Step;
+use crate::MaskDescriptor;
+
+/// day of the daily word, in the UTC calendar
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Date {
+    pub year: i64,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// today in UTC, from the clock
+    pub fn today() -> Self {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+
+        Self::from_days((secs / 86400) as i64)
+    }
+
+    /// days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    pub fn days(&self) -> i64 {
+        let year = self.year - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = (self.month as i64 + 9) % 12;
+        let day_of_year = (153 * month + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    pub fn from_days(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month + 2) / 5 + 1) as u8;
+        let month = if month < 10 { month + 3 } else { month - 9 } as u8;
+
+        Self { year: year_of_era + era * 400 + (month <= 2) as i64, month, day }
+    }
+
+    /// seed of the word of the day, the same for everyone sharing `seed`
+    pub fn seed(&self, seed: u64) -> u64 {
+        Rng::new(seed).next() ^ self.days() as u64
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// parse a date like 2024-03-01
+impl std::str::FromStr for Date {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ERROR: &str = "date must be like 2024-03-01";
+
+        let mut parts = s.splitn(3, '-');
+        let mut next = || parts.next().and_then(|part| part.parse::<i64>().ok()).ok_or(ERROR);
+        let (year, month, day) = (next()?, next()?, next()?);
+
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(ERROR);
+        }
+
+        let date = Self { year, month: month as u8, day: day as u8 };
+
+        // days past the end of the month, like 02-30, move to the next month
+        if Self::from_days(date.days()) != date {
+            return Err(ERROR);
+        }
+
+        Ok(date)
+    }
+}
+
+/// let the solver find the word picked with the options in the tries given, printing each turn and the share text
+pub fn solve(dir: &Path, rules: Rules, desc: Option<MaskDescriptor>, options: &play::Options) {
+    let (mut session, dico, answer) = match play::pick(dir, rules, desc, options.seed) {
+        Some(game) => game,
+        None => return,
+    };
+
+    let color = board::enabled();
+
+    for turn in 1..=options.tries {
+        let guess = match session.next_step(&dico.codes) {
+            Ok(Step::Obviously(id)) => id,
+            Ok(Step::Suggestion { best, .. }) => best,
+            // the answer is always a candidate
+            Ok(Step::NoWord) => {
+                eprintln!("{}", MaskError::NoCandidate);
+                return;
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                return;
+            }
+        };
+
+        let mut result = ResultState::new(dico.codes[guess].len(), false);
+        let played = rules.feedback(&dico.codes[guess], &dico.codes[answer], &mut result)
+            .and_then(|()| session.play(&dico.words[guess], result.clone()));
+
+        if let Err(err) = played {
+            eprintln!("{}", err);
+            return;
+        }
+
+        print!("{}", board::render(&session, color));
+
+        if session.complet() {
+            println!("Found in {} tries!", turn);
+            println!();
+            println!("{}", session.share(options.title.as_deref()));
+            return;
+        }
+    }
+
+    println!("Not found in {} tries, the word was {}", options.tries, dico.words[answer]);
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_dates() {
+        use super::Date;
+
+        let date: Date = "2024-03-01".parse().unwrap();
+        assert_eq!(date.days(), 19783);
+        assert_eq!(Date::from_days(19783), date);
+        assert_eq!(date.to_string(), "2024-03-01");
+        assert_eq!(Date::from_days(0).to_string(), "1970-01-01");
+
+        assert!("2024-02-29".parse::<Date>().is_ok());
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("2024-3".parse::<Date>().is_err());
+
+        let next: Date = "2024-03-02".parse().unwrap();
+        assert_ne!(date.seed(0), next.seed(0));
+        assert_ne!(date.seed(0), date.seed(1));
+    }
+}
@@ -1,38 +1,370 @@
-use std::io::{BufReader, BufRead};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{BufReader, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::alphabet::{Alphabet, AlphabetError};
 
 pub enum DicoError {
     InvalidChar(usize, char),
-    NoFile(char),
+    InvalidWord(String, char),
+    EmptyWord,
+    /// the word can't be played: games have words of 5 to 9 letters
+    InvalidLen(String),
+    NoFile(PathBuf),
     ReadingFile,
+    WritingFile,
+    Alphabet(AlphabetError),
+}
+
+/// words of a dictionary with the same len
+pub struct Dico {
+    pub alphabet: Alphabet,
+    /// words as written in the file
+    pub words: Vec<String>,
+    /// index of the letters of each word, this is what the solver works with
+    pub codes: Vec<Vec<u8>>,
 }
 
-/// return the dico plus id of best word if provided by the dico
-pub fn load(dico: char, word_len: u8) -> Result<Vec<String>, DicoError> {
-    let dico = BufReader::new(
-        std::fs::File::open(format!("dico/{}.txt", dico)).map_err(|_| DicoError::NoFile(dico))?
+impl Dico {
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn position(&self, word: &str) -> Option<usize> {
+        self.words.iter().position(|w| w == word)
+    }
+}
+
+/// path of the dictionary for words starting with `dico`
+pub fn path(dir: &Path, dico: &str) -> PathBuf {
+    dir.join(format!("{}.txt", dico))
+}
+
+/// return the words of dico with word_len letters, read with the alphabet of dir
+pub fn load(dir: &Path, dico: &str, word_len: u8) -> Result<Dico, DicoError> {
+    let alphabet = Alphabet::load(dir).map_err(DicoError::Alphabet)?;
+    let path = path(dir, dico);
+    let rows = BufReader::new(
+        std::fs::File::open(&path).map_err(|_| DicoError::NoFile(path))?
     ).lines().enumerate();
 
-    let mut matchs = Vec::with_capacity(500);
+    let mut words = Vec::with_capacity(500);
+    let mut codes = Vec::with_capacity(500);
     let word_len = word_len as usize;
 
-    for (line, row) in dico {
+    for (line, row) in rows {
         match row {
-            Ok(row) => if word_len == row.len() {
-                is_valid_word(&row).map_err(|c| DicoError::InvalidChar(line + 1, c))?;
-                matchs.push(row);
+            // a letter has at least one byte, so the word can't be shorter
+            Ok(row) => if row.len() >= word_len {
+                let code = alphabet.encode(&row).map_err(|c| DicoError::InvalidChar(line + 1, c))?;
+
+                if code.len() == word_len {
+                    words.push(row);
+                    codes.push(code);
+                }
             }
             Err(_) => return Err(DicoError::ReadingFile),
         }
     }
 
-    Ok(matchs)
+    Ok(Dico { alphabet, words, codes })
+}
+
+/// words with word_len letters of every dictionary in dir, when the first letter is unknown
+pub fn load_all(dir: &Path, word_len: u8) -> Result<Dico, DicoError> {
+    let alphabet = Alphabet::load(dir).map_err(DicoError::Alphabet)?;
+    let mut all = Dico { alphabet, words: Vec::new(), codes: Vec::new() };
+
+    for letter in letters(dir)? {
+        let dico = load(dir, &letter, word_len)?;
+        all.words.extend(dico.words);
+        all.codes.extend(dico.codes);
+    }
+
+    Ok(all)
+}
+
+/// insert word in its dictionary, keeping it sorted, and invalidate its bench id
+/// return false if the word was already there
+pub fn add(dir: &Path, word: &str) -> Result<bool, DicoError> {
+    let alphabet = Alphabet::load(dir).map_err(DicoError::Alphabet)?;
+    let code = alphabet.encode(word).map_err(|c| DicoError::InvalidWord(word.to_string(), c))?;
+
+    let first = code.first().ok_or(DicoError::EmptyWord)?;
+    let path = path(dir, alphabet.letter(*first));
+
+    if !(5..=9).contains(&code.len()) {
+        return Err(DicoError::InvalidLen(word.to_string()));
+    }
+
+    let mut rows = match std::fs::read_to_string(&path) {
+        Ok(content) => content.lines().map(str::to_string).collect(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(_) => return Err(DicoError::ReadingFile),
+    };
+
+    let header = header_len(&rows);
+    let index = match rows[header..].binary_search_by(|row| row.as_str().cmp(word)) {
+        Ok(_) => return Ok(false),
+        Err(index) => header + index,
+    };
+
+    rows.insert(index, word.to_string());
+
+    // ids of words with the same len are shifted, so the cached bench result is outdated
+    if header > 0 && (6..=9).contains(&code.len()) {
+        rows[code.len() - 6].clear();
+    }
+
+    let mut file = std::fs::File::create(&path).map_err(|_| DicoError::WritingFile)?;
+    for row in rows {
+        writeln!(file, "{}", row).map_err(|_| DicoError::WritingFile)?;
+    }
+
+    Ok(true)
+}
+
+/// all letters with a dictionary in dir
+pub fn letters(dir: &Path) -> Result<Vec<String>, DicoError> {
+    if !dir.is_dir() {
+        return Err(DicoError::NoFile(dir.to_path_buf()));
+    }
+
+    let alphabet = Alphabet::load(dir).map_err(DicoError::Alphabet)?;
+
+    Ok(alphabet.letters()
+        .filter(|letter| path(dir, letter).is_file())
+        .map(str::to_string)
+        .collect())
+}
+
+pub enum Issue {
+    InvalidChar(usize, char),
+    EmptyRow(usize),
+    /// line of the duplicate and line of the first occurrence
+    Duplicate(usize, usize),
+    Unsorted(usize),
+    Misplaced(usize),
+}
+
+#[derive(Default)]
+pub struct Report {
+    pub issues: Vec<Issue>,
+    /// count of valid words per len
+    pub lengths: BTreeMap<usize, usize>,
+}
+
+/// report every issue of a dictionary instead of stopping on the first one like `load`
+pub fn check(dir: &Path, dico: &str) -> Result<Report, DicoError> {
+    let alphabet = Alphabet::load(dir).map_err(DicoError::Alphabet)?;
+    let first = alphabet.index(dico);
+    let rows = read_rows(&path(dir, dico))?;
+    let header = header_len(&rows);
+
+    let mut report = Report::default();
+    let mut lines = HashMap::with_capacity(rows.len());
+    let mut previous: Option<&str> = None;
+
+    for (line, row) in rows.iter().enumerate().skip(header).map(|(i, row)| (i + 1, row)) {
+        if row.is_empty() {
+            report.issues.push(Issue::EmptyRow(line));
+            continue;
+        }
+
+        let code = match alphabet.encode(row) {
+            Ok(code) => code,
+            Err(c) => {
+                report.issues.push(Issue::InvalidChar(line, c));
+                continue;
+            }
+        };
+
+        if code.first().copied() != first {
+            report.issues.push(Issue::Misplaced(line));
+        }
+
+        if let Some(&first) = lines.get(row.as_str()) {
+            report.issues.push(Issue::Duplicate(line, first));
+            continue;
+        }
+
+        if previous.is_some_and(|previous| previous > row.as_str()) {
+            report.issues.push(Issue::Unsorted(line));
+        }
+
+        lines.insert(row.as_str(), line);
+        previous = Some(row);
+        *report.lengths.entry(code.len()).or_default() += 1;
+    }
+
+    Ok(report)
+}
+
+#[derive(Default)]
+pub struct FixReport {
+    /// rows without any valid word
+    pub dropped: Vec<String>,
+    /// count of words written in each file
+    pub written: Vec<(PathBuf, usize)>,
+}
+
+/// rewrite every dictionary of dir: words are normalized, moved in the file of
+/// their first letter, sorted and deduplicated while invalid rows are dropped
+pub fn fix(dir: &Path) -> Result<FixReport, DicoError> {
+    let alphabet = Alphabet::load(dir).map_err(DicoError::Alphabet)?;
+    let mut report = FixReport::default();
+    let mut words: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut files = HashMap::new();
+
+    for letter in letters(dir)? {
+        let rows = read_rows(&path(dir, &letter))?;
+        let header = header_len(&rows);
+
+        for row in &rows[header..] {
+            match normalize(row, &alphabet) {
+                Some((word, code)) => {
+                    let first = alphabet.letter(code[0]).to_string();
+                    words.entry(first).or_default().insert(word);
+                }
+                None if row.trim().is_empty() => (),
+                None => report.dropped.push(row.clone()),
+            }
+        }
+
+        files.insert(letter, rows);
+    }
+
+    for (letter, words) in words {
+        let path = path(dir, &letter);
+        let old_rows = files.remove(&letter).unwrap_or_default();
+        let header = header_len(&old_rows);
+
+        let unchanged = old_rows.len() == header + words.len()
+            && old_rows[header..].iter().zip(&words).all(|(old, new)| old == new);
+
+        if unchanged {
+            continue;
+        }
+
+        write_rows(&path, header, &words)?;
+        report.written.push((path, words.len()));
+    }
+
+    // files where every word has been dropped or moved
+    for (letter, rows) in files {
+        let header = header_len(&rows);
+
+        if rows.len() > header {
+            let path = path(dir, &letter);
+            write_rows(&path, header, &BTreeSet::new())?;
+            report.written.push((path, 0));
+        }
+    }
+
+    Ok(report)
 }
 
-fn is_valid_word(word: &str) -> Result<(), char> {
-    if let Some(invalid) = word.chars().find(|c| !('a'..='z').contains(c)) {
-        Err(invalid)
+/// add valid words to their dictionary, return the count of new words in each file
+pub fn merge(dir: &Path, words: &BTreeSet<String>) -> Result<Vec<(PathBuf, usize)>, DicoError> {
+    let alphabet = Alphabet::load(dir).map_err(DicoError::Alphabet)?;
+    let mut letters: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+    for word in words {
+        let code = alphabet.encode(word).map_err(|c| DicoError::InvalidWord(word.clone(), c))?;
+        let first = code.first().ok_or(DicoError::EmptyWord)?;
+        letters.entry(alphabet.letter(*first)).or_default().push(word);
+    }
+
+    std::fs::create_dir_all(dir).map_err(|_| DicoError::WritingFile)?;
+    let mut added = Vec::new();
+
+    for (letter, words) in letters {
+        let path = path(dir, letter);
+        let rows = match read_rows(&path) {
+            Ok(rows) => rows,
+            Err(DicoError::NoFile(_)) => Vec::new(),
+            Err(err) => return Err(err),
+        };
+
+        let header = header_len(&rows);
+        let mut merged: BTreeSet<String> = rows[header..].iter().cloned().collect();
+        let count = merged.len();
+        merged.extend(words.into_iter().map(str::to_string));
+
+        if merged.len() > count {
+            write_rows(&path, header, &merged)?;
+        }
+
+        added.push((path, merged.len() - count));
+    }
+
+    Ok(added)
+}
+
+/// write the sorted words of a dictionary, header rows are left empty
+/// since ids of the words may have moved
+fn write_rows(path: &Path, header: usize, words: &BTreeSet<String>) -> Result<(), DicoError> {
+    let mut file = std::fs::File::create(path).map_err(|_| DicoError::WritingFile)?;
+
+    for _ in 0..header {
+        writeln!(file).map_err(|_| DicoError::WritingFile)?;
+    }
+
+    for word in words {
+        writeln!(file, "{}", word).map_err(|_| DicoError::WritingFile)?;
+    }
+
+    Ok(())
+}
+
+/// lowercase word without the accents missing from the alphabet, with its code
+/// None if it still has chars out of the alphabet
+pub fn normalize(word: &str, alphabet: &Alphabet) -> Option<(String, Vec<u8>)> {
+    let mut normalized = String::with_capacity(word.len());
+
+    for c in word.trim().chars().flat_map(char::to_lowercase) {
+        match c {
+            c if alphabet.contains(c) => normalized.push(c),
+            'a'..='z' => normalized.push(c),
+            'à' | 'â' | 'ä' | 'á' | 'ã' | 'å' => normalized.push('a'),
+            'ç' => normalized.push('c'),
+            'é' | 'è' | 'ê' | 'ë' => normalized.push('e'),
+            'î' | 'ï' | 'í' | 'ì' => normalized.push('i'),
+            'ñ' => normalized.push('n'),
+            'ô' | 'ö' | 'ó' | 'ò' | 'õ' => normalized.push('o'),
+            'ù' | 'û' | 'ü' | 'ú' => normalized.push('u'),
+            'ÿ' | 'ý' => normalized.push('y'),
+            'æ' => normalized.push_str("ae"),
+            'œ' => normalized.push_str("oe"),
+            'ß' => normalized.push_str("ss"),
+            _ => return None,
+        }
+    }
+
+    match alphabet.encode(&normalized) {
+        Ok(code) if !code.is_empty() => Some((normalized, code)),
+        _ => None,
+    }
+}
+
+fn read_rows(path: &Path) -> Result<Vec<String>, DicoError> {
+    let file = std::fs::File::open(path).map_err(|_| DicoError::NoFile(path.to_path_buf()))?;
+
+    BufReader::new(file).lines()
+        .collect::<Result<_, _>>()
+        .map_err(|_| DicoError::ReadingFile)
+}
+
+/// number of header rows: the four first rows when they hold bench ids or nothing
+fn header_len(rows: &[String]) -> usize {
+    if rows.len() >= 4 && rows[..4].iter().all(|row| row.chars().all(|c| c.is_ascii_digit())) {
+        4
     } else {
-        Ok(())
+        0
     }
 }
 
@@ -40,8 +372,43 @@ impl std::fmt::Display for DicoError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             DicoError::InvalidChar(line, char) => write!(f, "Error on line {}: invalid char '{}'", line, char),
-            DicoError::NoFile(file) => write!(f, "No file named dico/{}.txt", file),
+            DicoError::InvalidWord(word, char) => write!(f, "Invalid word '{}': invalid char '{}'", word, char),
+            DicoError::EmptyWord => write!(f, "Can't add an empty word"),
+            DicoError::InvalidLen(word) => write!(f, "Invalid word '{}': words have from 5 to 9 letters", word),
+            DicoError::NoFile(path) => write!(f, "No file named {}", path.display()),
             DicoError::ReadingFile => write!(f, "Error while reading file!"),
+            DicoError::WritingFile => write!(f, "Error while writing file!"),
+            DicoError::Alphabet(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::InvalidChar(line, char) => write!(f, "line {}: invalid char '{}'", line, char),
+            Issue::EmptyRow(line) => write!(f, "line {}: empty row", line),
+            Issue::Duplicate(line, first) => write!(f, "line {}: duplicate of line {}", line, first),
+            Issue::Unsorted(line) => write!(f, "line {}: not sorted", line),
+            Issue::Misplaced(line) => write!(f, "line {}: word in the wrong file", line),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn normalize_word() {
+        use super::normalize;
+        use crate::alphabet::Alphabet;
+
+        let fr = Alphabet::default();
+        let normalized = |word| normalize(word, &fr).map(|(word, _)| word);
+
+        assert_eq!(normalized("Brûlions").as_deref(), Some("brulions"));
+        assert_eq!(normalized(" cœur\t").as_deref(), Some("coeur"));
+        assert_eq!(normalized("ÉLÈVE").as_deref(), Some("eleve"));
+        assert_eq!(normalized("porte-monnaie"), None);
+        assert_eq!(normalized(""), None);
+    }
+}
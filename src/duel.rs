@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use crate::ai::Strength;
+use crate::board;
+use crate::mask::ResultState;
+use crate::play;
+use crate::random::Rng;
+use crate::rules::Rules;
+use crate::MaskDescriptor;
+
+pub struct Player {
+    pub name: String,
+    /// strength of the solver playing for this player, None for a human
+    pub ai: Option<Strength>,
+    pub score: usize,
+}
+
+pub struct Options {
+    /// tries shared by the players for each word
+    pub tries: usize,
+    /// count of words, each player starts a round in turn
+    pub rounds: usize,
+    pub seed: u64,
+}
+
+/// players alternate guesses on the same word, the one who finds it scores the tries left
+pub fn run(dir: &Path, rules: Rules, desc: Option<MaskDescriptor>, mut players: Vec<Player>, options: &Options) {
+    let mut rng = Rng::new(options.seed);
+    let color = board::enabled();
+
+    'rounds: for round in 0..options.rounds {
+        let (mut session, dico, answer) = match play::pick(dir, rules, desc.clone(), rng.next()) {
+            Some(game) => game,
+            None => return,
+        };
+
+        println!();
+        println!("Round {}/{}: find the word of {} letters in {} tries", round + 1, options.rounds, session.desc().word_len(), options.tries);
+        println!("{}", play::known(&session));
+
+        for turn in 0..options.tries {
+            let current = current(round, turn, players.len());
+            let player = &players[current];
+
+            let guess = match player.ai {
+                Some(strength) => match strength.pick(&session, &dico, &mut rng) {
+                    Ok(guess) => {
+                        println!("{} plays {}", player.name, dico.words[guess]);
+                        guess
+                    }
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        break 'rounds;
+                    }
+                },
+                None => match play::read_guess(&session, &dico, &format!("{}, guess {}/{}: ", player.name, turn + 1, options.tries)) {
+                    Some(guess) => guess,
+                    None => break 'rounds,
+                },
+            };
+
+            let mut result = ResultState::new(dico.codes[guess].len(), false);
+            let played = rules.feedback(&dico.codes[guess], &dico.codes[answer], &mut result)
+                .and_then(|()| session.play(&dico.words[guess], result.clone()));
+
+            if let Err(err) = played {
+                eprintln!("{}", err);
+                break 'rounds;
+            }
+
+            print!("{}", board::render(&session, color));
+
+            if session.complet() {
+                let points = points(options.tries, turn);
+                players[current].score += points;
+                println!("{} found the word and scores {} points", players[current].name, points);
+                continue 'rounds;
+            }
+        }
+
+        println!("Nobody found the word, it was {}", dico.words[answer]);
+    }
+
+    println!();
+    print_scores(&players);
+}
+
+/// player of the turn, each round is started by the next player
+fn current(round: usize, turn: usize, players: usize) -> usize {
+    (round + turn) % players
+}
+
+/// points of the player who finds the word at turn (from 0): the tries left, this one included
+fn points(tries: usize, turn: usize) -> usize {
+    tries - turn
+}
+
+/// names of the players with the best score
+fn winners(players: &[Player]) -> Vec<&str> {
+    let best = players.iter().map(|player| player.score).max().unwrap_or_default();
+
+    players.iter()
+        .filter(|player| player.score == best)
+        .map(|player| player.name.as_str())
+        .collect()
+}
+
+fn print_scores(players: &[Player]) {
+    for player in players {
+        println!("{}: {} points", player.name, player.score);
+    }
+
+    let winners = winners(players);
+
+    match winners.as_slice() {
+        [winner] => println!("{} wins!", winner),
+        _ => println!("Draw between {}", winners.join(" and ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn turn_rotation() {
+        use super::current;
+
+        // each round starts with the next player, then they alternate
+        let turns: Vec<usize> = (0..4).map(|turn| current(0, turn, 3)).collect();
+        assert_eq!(turns, [0, 1, 2, 0]);
+
+        let turns: Vec<usize> = (0..4).map(|turn| current(1, turn, 3)).collect();
+        assert_eq!(turns, [1, 2, 0, 1]);
+
+        assert_eq!(current(3, 0, 3), 0);
+    }
+
+    #[test]
+    fn scoring() {
+        use super::{points, winners, Player};
+
+        assert_eq!(points(6, 0), 6);
+        assert_eq!(points(6, 5), 1);
+
+        let player = |name: &str, score| Player { name: name.to_string(), ai: None, score };
+        let mut players = vec![player("Alice", 6), player("Bob", 4)];
+        assert_eq!(winners(&players), ["Alice"]);
+
+        players[1].score += points(6, 4);
+        assert_eq!(winners(&players), ["Alice", "Bob"]);
+    }
+}
@@ -0,0 +1,155 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::ai::Strength;
+use crate::board;
+use crate::mask::{MaskError, ResultColor, ResultState};
+use crate::play;
+use crate::random::Rng;
+use crate::rules::Rules;
+use crate::MaskDescriptor;
+
+/// candidates grouped by the result they would give to the guess
+pub fn buckets(rules: Rules, dico: &[Vec<u8>], guess: &[u8], candidates: &[usize]) -> Result<Vec<(ResultState, Vec<usize>)>, MaskError> {
+    let mut buckets: HashMap<usize, (ResultState, Vec<usize>)> = HashMap::new();
+    let mut result = ResultState::new(guess.len(), false);
+
+    for &id in candidates {
+        rules.feedback(guess, &dico[id], &mut result)?;
+        buckets.entry(result.state_id())
+            .or_insert_with(|| (result.clone(), Vec::new()))
+            .1
+            .push(id);
+    }
+
+    Ok(buckets.into_values().collect())
+}
+
+/// result keeping the most candidates, ties go to the one with the fewest colors,
+/// then to the highest state id so that the same result is always given
+pub fn dodge(rules: Rules, dico: &[Vec<u8>], guess: &[u8], candidates: &[usize]) -> Result<(ResultState, Vec<usize>), MaskError> {
+    let colored = |result: &ResultState| result.colors().iter().filter(|&&color| color != ResultColor::Blue).count();
+
+    buckets(rules, dico, guess, candidates)?
+        .into_iter()
+        .max_by_key(|(result, bucket)| (bucket.len(), Reverse(colored(result)), result.state_id()))
+        .ok_or(MaskError::NoCandidate)
+}
+
+/// the host picks no word and answers each guess with the result keeping the most words,
+/// guesses are given by the player or by the solver at `ai` strength
+pub fn run(dir: &Path, rules: Rules, desc: Option<MaskDescriptor>, ai: Option<Strength>, tries: usize, seed: u64) {
+    // the picked word is ignored, only the words matching the descriptor are needed
+    let (mut session, dico, _) = match play::pick(dir, rules, desc, seed) {
+        Some(game) => game,
+        None => return,
+    };
+
+    let mut candidates = match session.candidates(&dico.codes) {
+        Ok(candidates) => candidates,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    let mut rng = Rng::new(seed);
+    let color = board::enabled();
+
+    println!("Find the word of {} letters in {} tries, if there is one", session.desc().word_len(), tries);
+    println!("{}", play::known(&session));
+
+    for turn in 1..=tries {
+        let guess = match ai {
+            Some(strength) => match strength.pick(&session, &dico, &mut rng) {
+                Ok(guess) => guess,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return;
+                }
+            },
+            None => match play::read_guess(&session, &dico, &format!("Guess {}/{}: ", turn, tries)) {
+                Some(guess) => guess,
+                None => break,
+            },
+        };
+
+        let result = match dodge(rules, &dico.codes, &dico.codes[guess], &candidates) {
+            Ok((result, left)) => {
+                candidates = left;
+                result
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                return;
+            }
+        };
+
+        if let Err(err) = session.play(&dico.words[guess], result.clone()) {
+            eprintln!("{}", err);
+            return;
+        }
+
+        print!("{}", board::render(&session, color));
+
+        println!("{} words left", candidates.len());
+
+        if session.complet() {
+            println!("Found in {} tries!", turn);
+            println!();
+            println!("{}", session.share(None));
+            return;
+        }
+    }
+
+    println!("{} words were still possible, like {}", candidates.len(), dico.words[candidates[0]]);
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn dodge_guess() {
+        use super::{buckets, dodge};
+        use crate::alphabet::Alphabet;
+        use crate::rules::Rules;
+
+        let fr = Alphabet::default();
+        let dico: Vec<Vec<u8>> = ["carte", "tarte", "porte", "verte", "zozos"].iter()
+            .map(|word| fr.encode(word).unwrap())
+            .collect();
+
+        let candidates = [0, 1, 2, 3, 4];
+        assert_eq!(buckets(Rules::WORDLE, &dico, &dico[0], &candidates).unwrap().len(), 4);
+
+        // "carte" against tarte, porte and verte: the most words stay with "..rte"
+        let (result, left) = dodge(Rules::WORDLE, &dico, &dico[0], &candidates).unwrap();
+        assert_eq!(result.to_string(), "bbrrr");
+        assert_eq!(left, [2, 3]);
+
+        // a single word left is never given away when another result keeps as many
+        let (result, left) = dodge(Rules::WORDLE, &dico, &dico[2], &[2, 4]).unwrap();
+        assert!(!result.complet());
+        assert_eq!(left, [4]);
+    }
+
+    #[test]
+    fn dodge_ties() {
+        use super::dodge;
+        use crate::alphabet::Alphabet;
+        use crate::rules::Rules;
+
+        let fr = Alphabet::default();
+        let dico: Vec<Vec<u8>> = ["carte", "colin", "ertas"].iter()
+            .map(|word| fr.encode(word).unwrap())
+            .collect();
+
+        // "rbbbb" and "byyyy" keep a word each, the one with the fewest colors is given
+        let (result, left) = dodge(Rules::WORDLE, &dico, &dico[0], &[1, 2]).unwrap();
+        assert_eq!(result.to_string(), "rbbbb");
+        assert_eq!(left, [1]);
+
+        let (result, _) = dodge(Rules::WORDLE, &dico, &dico[0], &[2, 1]).unwrap();
+        assert_eq!(result.to_string(), "rbbbb");
+    }
+}
@@ -0,0 +1,76 @@
+use crate::mask;
+use crate::output::Event;
+
+/// grades of the guesses of a player, with the metric of `Rules::scores`
+#[derive(Default)]
+pub struct Hint {
+    /// score of each graded guess and score of the best word of the turn
+    turns: Vec<(f32, f32)>,
+}
+
+pub struct Grade {
+    /// average count of words left by the guess
+    pub score: f32,
+    pub best: usize,
+    pub best_score: f32,
+}
+
+impl Hint {
+    /// grade the guess with the scores of the turn, see `Session::scores`
+    pub fn grade(&mut self, scores: &[f32], guess: usize) -> Option<Grade> {
+        let (best, best_score) = mask::best(scores)?;
+
+        // NaN when the guess leaves a single word whatever the answer is
+        let score = match scores.get(guess)? {
+            score if score.is_nan() => 1.0,
+            &score => score,
+        };
+
+        self.turns.push((score, best_score));
+        Some(Grade { score, best, best_score })
+    }
+
+    /// share of the best score reached on average, 100% if every guess was the best
+    pub fn skill(&self) -> Option<f32> {
+        if self.turns.is_empty() {
+            return None;
+        }
+
+        let sum: f32 = self.turns.iter().map(|&(score, best)| (best / score).min(1.0)).sum();
+        Some(sum / self.turns.len() as f32 * 100.0)
+    }
+
+    pub fn summary(&self) -> Option<Event<'static>> {
+        let skill = self.skill()?;
+        let optimal = self.turns.iter().filter(|(score, best)| score <= best).count();
+
+        Some(Event::Skill { skill, graded: self.turns.len(), optimal })
+    }
+}
+
+impl Grade {
+    /// event for the word played, with the best word of the turn
+    pub fn event<'a>(&self, word: &'a str, best: &'a str) -> Event<'a> {
+        Event::Grade { word, score: self.score, best, best_score: self.best_score }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn grade_guesses() {
+        use super::Hint;
+
+        let scores = [4.0, 2.0, f32::NAN, 8.0];
+        let mut hint = Hint::default();
+
+        let grade = hint.grade(&scores, 3).unwrap();
+        assert_eq!((grade.score, grade.best, grade.best_score), (8.0, 1, 2.0));
+
+        let grade = hint.grade(&scores, 2).unwrap();
+        assert_eq!(grade.score, 1.0);
+
+        assert_eq!(hint.skill(), Some(62.5));
+        assert!(hint.grade(&scores, 4).is_none());
+    }
+}
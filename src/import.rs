@@ -0,0 +1,413 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::alphabet::Alphabet;
+use crate::dico;
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    /// hunspell .dic file, expanded with the .aff file next to it
+    Hunspell,
+    /// tab, comma or semicolon separated values with a header, like Lexique
+    Table,
+    /// one word per row
+    Plain,
+}
+
+impl Format {
+    /// guess the format from the extension of the file
+    pub fn guess(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("dic") => Format::Hunspell,
+            Some("tsv" | "csv") => Format::Table,
+            _ => Format::Plain,
+        }
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hunspell" => Ok(Format::Hunspell),
+            "table" | "lexique" | "csv" | "tsv" => Ok(Format::Table),
+            "plain" => Ok(Format::Plain),
+            _ => Err("format must be one of hunspell, table or plain"),
+        }
+    }
+}
+
+pub struct Options {
+    pub min_len: usize,
+    pub max_len: usize,
+    /// column of the words in a table, by name or index
+    pub word_column: String,
+    /// column of the frequencies in a table, by name or index
+    pub freq_column: Option<String>,
+    pub min_freq: f32,
+}
+
+/// normalized words of the lexicon, the frequencies are only used to filter them
+pub type Words = BTreeSet<String>;
+
+/// words are normalized for the alphabet of the dictionaries they will be merged in
+pub fn read(path: &Path, format: Format, options: &Options, alphabet: &Alphabet) -> Result<Words, ImportError> {
+    let raw: Vec<(String, Option<f32>)> = match format {
+        Format::Plain => read_file(path, Encoding::Utf8)?.lines().map(|row| (row.trim().to_string(), None)).collect(),
+        Format::Table => read_table(&read_file(path, Encoding::Utf8)?, options)?,
+        Format::Hunspell => {
+            let aff = path.with_extension("aff");
+            let encoding = Encoding::of_affixes(&read_bytes(&aff)?)?;
+
+            // both files are written in the encoding given by the affix file
+            let dic = read_file(path, encoding)?;
+            read_hunspell(&dic, &read_file(&aff, encoding)?)?.into_iter().map(|word| (word, None)).collect()
+        }
+    };
+
+    let mut words = BTreeMap::new();
+
+    for (word, freq) in raw {
+        // proper nouns are not allowed
+        if word.chars().next().is_none_or(char::is_uppercase) {
+            continue;
+        }
+
+        let word = match dico::normalize(&word, alphabet) {
+            Some((word, code)) if (options.min_len..=options.max_len).contains(&code.len()) => word,
+            _ => continue,
+        };
+
+        // accents are removed, so frequencies of words now written the same way are summed
+        let entry = words.entry(word).or_insert(None);
+        if let Some(freq) = freq {
+            *entry = Some(entry.unwrap_or(0.0) + freq);
+        }
+    }
+
+    Ok(words.into_iter()
+        .filter(|(_, freq)| freq.is_none_or(|freq| freq >= options.min_freq))
+        .map(|(word, _)| word)
+        .collect())
+}
+
+/// encodings of the hunspell dictionaries that can be read
+#[derive(Clone, Copy)]
+enum Encoding {
+    Utf8,
+    /// ISO8859-1
+    Latin1,
+    /// ISO8859-15, like latin-1 with 'œ' and '€'
+    Latin9,
+}
+
+impl Encoding {
+    /// encoding given by the "SET" row of an affix file, UTF-8 without it
+    fn of_affixes(aff: &[u8]) -> Result<Self, ImportError> {
+        let set = aff.split(|&b| b == b'\n')
+            .find_map(|row| row.strip_prefix(b"SET "))
+            .map(|name| String::from_utf8_lossy(name).trim().to_uppercase());
+
+        match set.as_deref() {
+            None | Some("UTF-8") => Ok(Encoding::Utf8),
+            Some("ISO8859-1" | "ISO-8859-1") => Ok(Encoding::Latin1),
+            Some("ISO8859-15" | "ISO-8859-15") => Ok(Encoding::Latin9),
+            Some(name) => Err(ImportError::UnknownEncoding(name.to_string())),
+        }
+    }
+
+    fn decode(&self, bytes: Vec<u8>) -> Result<String, std::string::FromUtf8Error> {
+        let latin9 = |b: u8| match b {
+            0xa4 => '€',
+            0xa6 => 'Š',
+            0xa8 => 'š',
+            0xb4 => 'Ž',
+            0xb8 => 'ž',
+            0xbc => 'Œ',
+            0xbd => 'œ',
+            0xbe => 'Ÿ',
+            b => b as char,
+        };
+
+        match self {
+            Encoding::Utf8 => String::from_utf8(bytes),
+            // every byte is the code point of its char
+            Encoding::Latin1 => Ok(bytes.into_iter().map(char::from).collect()),
+            Encoding::Latin9 => Ok(bytes.into_iter().map(latin9).collect()),
+        }
+    }
+}
+
+fn read_bytes(path: &Path) -> Result<Vec<u8>, ImportError> {
+    std::fs::read(path).map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => ImportError::NoFile(path.to_path_buf()),
+        kind => ImportError::ReadingFile(path.to_path_buf(), kind),
+    })
+}
+
+fn read_file(path: &Path, encoding: Encoding) -> Result<String, ImportError> {
+    encoding.decode(read_bytes(path)?).map_err(|err| {
+        let valid = &err.as_bytes()[..err.utf8_error().valid_up_to()];
+        let line = valid.iter().filter(|&&b| b == b'\n').count() + 1;
+        ImportError::InvalidEncoding(path.to_path_buf(), line)
+    })
+}
+
+fn read_table(content: &str, options: &Options) -> Result<Vec<(String, Option<f32>)>, ImportError> {
+    let mut rows = content.lines();
+    let header = rows.next().unwrap_or_default();
+
+    let separator = ['\t', ';', ',']
+        .into_iter()
+        .find(|&sep| header.contains(sep))
+        .unwrap_or('\t');
+
+    let columns: Vec<&str> = header.split(separator).map(str::trim).collect();
+    let column = |name: &str| {
+        columns.iter().position(|&column| column == name)
+            .or_else(|| name.parse().ok())
+            .ok_or_else(|| ImportError::UnknownColumn(name.to_string()))
+    };
+
+    let word_column = column(&options.word_column)?;
+    let freq_column = options.freq_column.as_deref().map(column).transpose()?;
+
+    let mut words = Vec::new();
+
+    for row in rows {
+        let cells: Vec<&str> = row.split(separator).map(str::trim).collect();
+
+        if let Some(word) = cells.get(word_column) {
+            let freq = freq_column
+                .and_then(|column| cells.get(column))
+                .and_then(|freq| freq.replace(',', ".").parse().ok());
+
+            words.push((word.to_string(), freq));
+        }
+    }
+
+    Ok(words)
+}
+
+enum FlagKind {
+    Char,
+    Long,
+    Num,
+}
+
+impl FlagKind {
+    fn split(&self, flags: &str) -> Vec<String> {
+        match self {
+            FlagKind::Char => flags.chars().map(String::from).collect(),
+            FlagKind::Long => flags.chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|flag| flag.iter().collect())
+                .collect(),
+            FlagKind::Num => flags.split(',').map(str::to_string).collect(),
+        }
+    }
+}
+
+struct Affix {
+    prefix: bool,
+    cross: bool,
+    strip: String,
+    add: String,
+    /// each char of the condition: allowed (or forbidden if negated) chars, empty for any
+    condition: Vec<(Vec<char>, bool)>,
+}
+
+impl Affix {
+    fn apply(&self, word: &str) -> Option<String> {
+        let chars: Vec<char> = word.chars().collect();
+
+        if chars.len() < self.condition.len() {
+            return None;
+        }
+
+        let tested = if self.prefix {
+            &chars[..self.condition.len()]
+        } else {
+            &chars[chars.len() - self.condition.len()..]
+        };
+
+        let matching = tested.iter().zip(&self.condition).all(|(c, (set, negated))| {
+            set.is_empty() || set.contains(c) != *negated
+        });
+
+        if !matching {
+            return None;
+        }
+
+        if self.prefix {
+            word.strip_prefix(self.strip.as_str()).map(|word| format!("{}{}", self.add, word))
+        } else {
+            word.strip_suffix(self.strip.as_str()).map(|word| format!("{}{}", word, self.add))
+        }
+    }
+}
+
+fn parse_condition(condition: &str) -> Vec<(Vec<char>, bool)> {
+    let mut parsed = Vec::new();
+    let mut chars = condition.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => parsed.push((Vec::new(), false)),
+            '[' => {
+                let mut set: Vec<char> = chars.by_ref().take_while(|&c| c != ']').collect();
+                let negated = set.first() == Some(&'^');
+
+                if negated {
+                    set.remove(0);
+                }
+
+                parsed.push((set, negated));
+            }
+            c => parsed.push((vec![c], false)),
+        }
+    }
+
+    parsed
+}
+
+fn read_affixes(aff: &str) -> Result<(FlagKind, HashMap<String, Vec<Affix>>), ImportError> {
+    let mut kind = FlagKind::Char;
+    let mut affixes: HashMap<String, Vec<Affix>> = HashMap::new();
+    let mut cross = HashMap::new();
+
+    for (line, row) in aff.lines().enumerate() {
+        let fields: Vec<&str> = row.split_whitespace().collect();
+
+        match fields.as_slice() {
+            ["FLAG", "long", ..] => kind = FlagKind::Long,
+            ["FLAG", "num", ..] => kind = FlagKind::Num,
+            // header of an affix class: "SFX <flag> <cross product> <count>"
+            [fx @ ("PFX" | "SFX"), flag, product, count] if count.parse::<usize>().is_ok() => {
+                cross.insert((*fx, flag.to_string()), *product == "Y");
+            }
+            // rule of an affix class: "SFX <flag> <strip> <add> <condition>"
+            [fx @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                let &cross = cross.get(&(*fx, flag.to_string())).ok_or(ImportError::InvalidAffix(line + 1))?;
+
+                // continuation classes of the added part are not supported
+                let add = add.split('/').next().unwrap_or_default();
+
+                affixes.entry(flag.to_string()).or_default().push(Affix {
+                    prefix: *fx == "PFX",
+                    cross,
+                    strip: if *strip == "0" { String::new() } else { strip.to_string() },
+                    add: if add == "0" { String::new() } else { add.to_string() },
+                    condition: parse_condition(rest.first().unwrap_or(&".")),
+                });
+            }
+            _ => (),
+        }
+    }
+
+    Ok((kind, affixes))
+}
+
+/// every form of the dictionary, with prefixes and suffixes applied
+fn read_hunspell(dic: &str, aff: &str) -> Result<Vec<String>, ImportError> {
+    let (kind, affixes) = read_affixes(aff)?;
+    let mut words = Vec::new();
+
+    // first row is the approximate count of words
+    for row in dic.lines().skip(1) {
+        let entry = row.split(['\t', ' ']).next().unwrap_or_default();
+        let (word, flags) = entry.split_once('/').unwrap_or((entry, ""));
+
+        if word.is_empty() {
+            continue;
+        }
+
+        let rules: Vec<&Affix> = kind.split(flags).iter()
+            .filter_map(|flag| affixes.get(flag))
+            .flatten()
+            .collect();
+
+        let suffixed: Vec<(String, bool)> = rules.iter()
+            .filter(|affix| !affix.prefix)
+            .filter_map(|affix| affix.apply(word).map(|form| (form, affix.cross)))
+            .collect();
+
+        for prefix in rules.iter().filter(|affix| affix.prefix) {
+            words.extend(prefix.apply(word));
+
+            if prefix.cross {
+                let crossed = suffixed.iter()
+                    .filter(|(_, cross)| *cross)
+                    .filter_map(|(form, _)| prefix.apply(form));
+
+                words.extend(crossed);
+            }
+        }
+
+        words.extend(suffixed.into_iter().map(|(form, _)| form));
+        words.push(word.to_string());
+    }
+
+    Ok(words)
+}
+
+pub enum ImportError {
+    NoFile(PathBuf),
+    ReadingFile(PathBuf, std::io::ErrorKind),
+    /// the file isn't valid UTF-8 from this line
+    InvalidEncoding(PathBuf, usize),
+    UnknownEncoding(String),
+    InvalidAffix(usize),
+    UnknownColumn(String),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::NoFile(path) => write!(f, "No file named {}", path.display()),
+            ImportError::ReadingFile(path, kind) => write!(f, "Error while reading {}: {}", path.display(), kind),
+            ImportError::InvalidEncoding(path, line) => write!(f, "Error on line {} of {}: invalid UTF-8, convert the file first", line, path.display()),
+            ImportError::UnknownEncoding(name) => write!(f, "Unsupported encoding '{}' in the affix file, only UTF-8, ISO8859-1 and ISO8859-15 are read", name),
+            ImportError::InvalidAffix(line) => write!(f, "Error on line {} of the affix file: rule without header", line),
+            ImportError::UnknownColumn(column) => write!(f, "No column named '{}'", column),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn expand_hunspell() {
+        use super::read_hunspell;
+
+        let aff = "SET UTF-8\n\
+            PFX R Y 1\n\
+            PFX R 0 re .\n\
+            SFX S Y 2\n\
+            SFX S 0 s [^s]\n\
+            SFX S er ons er\n";
+
+        let mut words = read_hunspell("2\nchanter/RS\nbras/S\n", aff).unwrap_or_default();
+        words.sort();
+
+        assert_eq!(words, ["bras", "chanter", "chanters", "chantons", "rechanter", "rechanters", "rechantons"]);
+    }
+
+    #[test]
+    fn decode_affixes() {
+        use super::Encoding;
+
+        let aff = b"# latin-1\nSET ISO8859-1\nSFX S Y 1\n";
+        let encoding = Encoding::of_affixes(aff).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(encoding.decode(b"\xe9l\xe8ve".to_vec()).unwrap(), "élève");
+
+        let encoding = Encoding::of_affixes(b"SET ISO8859-15\n").unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(encoding.decode(b"c\xbdur".to_vec()).unwrap(), "cœur");
+
+        let encoding = Encoding::of_affixes(b"SFX S Y 1\n").unwrap_or_else(|err| panic!("{}", err));
+        assert!(encoding.decode(b"\xe9l\xe8ve".to_vec()).is_err());
+        assert!(Encoding::of_affixes(b"SET KOI8-R\n").is_err());
+    }
+}
@@ -1,30 +1,112 @@
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use structopt::StructOpt;
+use alphabet::Alphabet;
+use rules::Rules;
+use session::{Session, Step};
+mod ai;
+mod alphabet;
+mod analyze;
+mod board;
+mod daily;
 mod mask;
 mod dico;
+mod duel;
+mod evil;
+mod session;
+mod hint;
+mod import;
+mod output;
+mod play;
+mod random;
+mod rules;
+mod serve;
+mod tui;
 
+#[derive(Clone)]
 struct MaskDescriptor {
-    pub dico: char,
+    /// revealed first letter, it may be made of many chars for some alphabets
+    pub dico: Option<String>,
+    /// count of letters after the revealed one
     pub len: u8,
+    /// known letters at their place, like "b..l....", the letter and len are set
+    /// from it once the alphabet is known, see `Session::new`
+    pub pattern: Option<String>,
+}
+
+impl MaskDescriptor {
+    pub fn word_len(&self) -> u8 {
+        self.len + self.dico.is_some() as u8
+    }
+
+    /// dictionary of the first letter, or all of them if it's unknown
+    pub fn load(&self, dir: &Path) -> Result<dico::Dico, dico::DicoError> {
+        match &self.dico {
+            Some(letter) => dico::load(dir, letter, self.word_len()),
+            None => dico::load_all(dir, self.word_len()),
+        }
+    }
+
+    /// descriptor of the game of the word, its first letter is revealed if the rules do
+    pub fn of_word(word: &str, alphabet: &Alphabet, rules: Rules) -> Result<Self, String> {
+        let code = alphabet.encode(word).map_err(|c| format!("Invalid word '{}': invalid char '{}'", word, c))?;
+        let dico = code.first()
+            .filter(|_| rules.reveal_first())
+            .map(|&c| alphabet.letter(c).to_string());
+        let len = code.len() - dico.is_some() as usize;
+
+        if (5..=8).contains(&len) {
+            Ok(Self { dico, len: len as u8, pattern: None })
+        } else {
+            Err("word don't have the correct length!".to_string())
+        }
+    }
+
+    /// dictionaries searched by a query: the one of its first letter, or all of them if it's unknown
+    pub fn of_mask(mask: &mask::Mask, alphabet: &Alphabet) -> Self {
+        let dico = mask.first().map(|first| alphabet.letter(first).to_string());
+        let len = (mask.word_len() - dico.is_some() as usize) as u8;
+
+        Self { dico, len, pattern: None }
+    }
+
+    /// file or directory the words are loaded from
+    pub fn path(&self, dir: &Path) -> PathBuf {
+        match &self.dico {
+            Some(letter) => dico::path(dir, letter),
+            None => dir.to_path_buf(),
+        }
+    }
+}
+
+impl std::fmt::Display for MaskDescriptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.pattern {
+            Some(pattern) => write!(f, "{}", pattern),
+            None => write!(f, "{}{}", self.dico.as_deref().unwrap_or_default(), self.len),
+        }
+    }
 }
 
 impl std::str::FromStr for MaskDescriptor {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 2 {
-            Err("MaskDescriptor must be of len 2!")
-        } else {
-            let mut iter = s.chars();
-            let dico = iter.next().unwrap();
+        if s.contains('.') {
+            return Ok(Self { dico: None, len: 0, pattern: Some(s.to_string()) });
+        }
+
+        let mut iter = s.chars();
 
-            let len = match iter.next().unwrap() {
-                len @ '5'..='8' => Ok(len as u8 - b'0'),
-                _ => Err("len must be between 5 and 8 included"),
-            }?;
+        let len = match iter.next_back() {
+            Some(len @ '5'..='8') => Ok(len as u8 - b'0'),
+            _ => Err("len must be between 5 and 8 included"),
+        }?;
 
-            Ok(Self { dico, len })
+        match iter.as_str() {
+            "" => Ok(Self { dico: None, len, pattern: None }),
+            dico => Ok(Self { dico: Some(dico.to_string()), len, pattern: None }),
         }
     }
 }
@@ -32,137 +114,1070 @@ impl std::str::FromStr for MaskDescriptor {
 #[derive(StructOpt)]
 /// is some flag is provided, search pattern will be ignored
 struct Args {
-    /// shound match pattern /[a-z][5-8]/
+    /// shound match pattern /<letter>[5-8]/, letters are the ones of the alphabet.
+    /// Without revealed letter, like in wordle, only the len is given.
+    /// Extra revealed letters are given with a pattern like "b..l...."
     pub search: Option<MaskDescriptor>,
 
+    #[structopt(long, default_value = "motus")]
+    /// motus (or tusmo), wordle or mastermind (counts of colors only), followed by
+    /// +first or -first to reveal the first letter or not. Without revealed letter, words have 5 letters by default
+    pub rules: Rules,
+
     #[structopt(long, short)]
     /// You want play but you haven't friends ? So just give it a word
     pub auto: Option<String>,
 
     #[structopt(long, short)]
     /// bench a specific dictionary. <bench> must be letter
-    pub bench: Option<char>,
+    pub bench: Option<String>,
+
+    #[structopt(long)]
+    /// grade the words played against the best one, for solved games and the play command
+    pub hint: bool,
+
+    #[structopt(long, default_value = "text")]
+    /// text, or json to print one json object per event for the solver, --bench, query and analyze
+    pub format: output::Format,
+
+    #[structopt(long, short)]
+    /// play in a full screen: pick the word among the suggestions and color its letters
+    pub tui: bool,
+
+    #[structopt(long, short, parse(from_os_str))]
+    /// save the game after each turn, see the resume command
+    pub save: Option<PathBuf>,
+
+    #[structopt(long, default_value = "dico", parse(from_os_str))]
+    /// directory of the dictionaries, with their alphabet in alphabet.txt
+    pub dico_dir: PathBuf,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
 }
 
-// use https://www.tusmo.xyz/s3da53bb 4 tests
-// inventees
+#[derive(StructOpt)]
+enum Command {
+    /// print all words matching a pattern, like "b..ll... +e -xyz 3^ae"
+    ///
+    /// '.' for unknown letters, '+' for letters present elsewhere,
+    /// '-' for letters without other occurrence and '<pos>^' for letters not at <pos>
+    Query {
+        spec: String,
+    },
 
-fn main() {
-    let mut args = Args::from_args();
+    /// print the words remaining and the best word after the given turns, without any prompt
+    Solve {
+        /// like the search pattern
+        search: MaskDescriptor,
 
-    if let Some(word) = &args.auto {
-        let word_len = word.len();
+        #[structopt(parse(try_from_str = parse_turn))]
+        /// turns played, like brulions:rybyybbb
+        turns: Vec<(String, mask::ResultState)>,
+    },
 
-        if !(6..=9).contains(&word_len) {
-            eprintln!("word don't have the correct length!");
-            args.search = None;
-            args.bench = None;
-        } else {
-            let dico = word.chars().next().unwrap();
-            args.search = Some(MaskDescriptor { dico, len: word_len as u8 - 1 });
+    /// resume a game saved with --save, it will keep saving in the same file
+    Resume {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+
+    /// guess a word picked by the computer, at random among the words matching the descriptor
+    Play {
+        /// like the search pattern, random first letter and len if not provided
+        search: Option<MaskDescriptor>,
+
+        #[structopt(long, default_value = "6")]
+        tries: usize,
+
+        #[structopt(long)]
+        /// seed of the random word, to replay the same game
+        seed: Option<u64>,
+    },
+
+    /// players alternate guesses on the same word, the one who finds it scores the tries left
+    Duel {
+        /// like the search pattern, random first letter and len if not provided
+        search: Option<MaskDescriptor>,
+
+        #[structopt(long = "player")]
+        /// name of a human player, repeat it for each player. Two players by default, one with --ai
+        players: Vec<String>,
+
+        #[structopt(long)]
+        /// add the solver as the last player, playing at this strength: random, greedy, top<k> (like top5) or optimal
+        ai: Option<ai::Strength>,
+
+        #[structopt(long, default_value = "6")]
+        /// tries shared by the players for each word
+        tries: usize,
+
+        #[structopt(long, default_value = "2")]
+        /// count of words, each player starts a round in turn
+        rounds: usize,
+
+        #[structopt(long)]
+        /// seed of the random words, to replay the same duel
+        seed: Option<u64>,
+    },
+
+    /// the host picks no word and answers each guess with the result keeping the most words
+    Evil {
+        /// like the search pattern, random first letter and len if not provided
+        search: Option<MaskDescriptor>,
+
+        #[structopt(long)]
+        /// let the solver guess at this strength: random, greedy, top<k> (like top5) or optimal
+        ai: Option<ai::Strength>,
+
+        #[structopt(long, default_value = "6")]
+        tries: usize,
+
+        #[structopt(long)]
+        /// seed of the random descriptor and of the solver
+        seed: Option<u64>,
+    },
+
+    /// guess the word of the day, the same for everyone using the same seed
+    Daily {
+        /// like the search pattern, random first letter and len if not provided
+        search: Option<MaskDescriptor>,
+
+        #[structopt(long)]
+        /// day of the word, like 2024-03-01, today (UTC) if not provided
+        date: Option<daily::Date>,
+
+        #[structopt(long, default_value = "0")]
+        /// seed shared by the team, to have another word than the other teams
+        seed: u64,
+
+        #[structopt(long, default_value = "6")]
+        tries: usize,
+
+        #[structopt(long)]
+        /// let the solver find the word instead of guessing it
+        solve: bool,
+    },
+
+    /// replay a finished game: words left, best word and luck vs skill of each guess
+    Analyze {
+        /// word to find
+        answer: String,
+
+        /// words played, in order
+        guesses: Vec<String>,
+
+        #[structopt(long, parse(from_os_str))]
+        /// also write the report as a Markdown table in this file
+        markdown: Option<PathBuf>,
+    },
+
+    /// answer the requests of other programs, see the README for the protocol
+    Serve {
+        #[structopt(long)]
+        /// read one json request per line on stdin and answer each one with a line on stdout
+        stdio: bool,
+
+        #[structopt(long)]
+        /// listen for the requests of the http api on this address, like 127.0.0.1:8080
+        http: Option<String>,
+    },
+
+    /// maintain the dictionaries
+    Dico(DicoCommand),
+}
+
+impl Command {
+    /// name of the commands printing only text, whatever the format
+    fn text_only(&self) -> Option<&'static str> {
+        match self {
+            Command::Play { .. } => Some("play"),
+            Command::Duel { .. } => Some("duel"),
+            Command::Evil { .. } => Some("evil"),
+            Command::Daily { .. } => Some("daily"),
+            Command::Serve { .. } => Some("serve"),
+            Command::Dico(_) => Some("dico"),
+            Command::Query { .. } | Command::Solve { .. } | Command::Resume { .. } | Command::Analyze { .. } => None,
         }
     }
+}
 
-    if let Some(mask_desc) = args.search {
-        let start = std::time::Instant::now();
-        let dico = match dico::load(mask_desc.dico, mask_desc.len + 1) {
-            Ok(dico) => dico,
-            Err(error) => {
-                eprintln!("{}", error);
+#[derive(StructOpt)]
+enum DicoCommand {
+    /// add a word to its dictionary, keeping it sorted
+    Add {
+        word: String,
+    },
+
+    /// report invalid, duplicated, unsorted and misplaced words, plus counts per len
+    Check {
+        /// dictionaries to check, all of them if empty
+        letters: Vec<String>,
+    },
+
+    /// normalize, move, sort and deduplicate words of every dictionary, dropping invalid ones
+    Fix,
+
+    /// import the words of a lexicon in the dictionaries
+    Import {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+
+        #[structopt(long)]
+        /// hunspell (.aff file next to the .dic one), table (tsv or csv, like Lexique) or plain,
+        /// guessed from the extension if not provided
+        format: Option<import::Format>,
+
+        #[structopt(long, default_value = "6")]
+        min_len: usize,
+
+        #[structopt(long, default_value = "10")]
+        max_len: usize,
+
+        #[structopt(long, default_value = "ortho")]
+        /// column of the words in a table, by name or index
+        word_column: String,
+
+        #[structopt(long)]
+        /// column of the frequencies in a table, by name or index
+        freq_column: Option<String>,
+
+        #[structopt(long, default_value = "0")]
+        /// words less frequent are ignored, only for sources with frequencies
+        min_freq: f32,
+    },
+}
+
+/// turn given as "<word>:<result>"
+fn parse_turn(s: &str) -> Result<(String, mask::ResultState), String> {
+    let (word, result) = s.split_once(':').ok_or_else(|| format!("'{}' must be like <word>:<result>", s))?;
+    let result = mask::ResultState::try_from(result).map_err(|err| err.to_string())?;
+
+    Ok((word.to_lowercase(), result))
+}
+
+fn import_dico(dir: &Path, file: &Path, format: Option<import::Format>, options: &import::Options) {
+    let format = format.unwrap_or_else(|| import::Format::guess(file));
+
+    let alphabet = match Alphabet::load(dir) {
+        Ok(alphabet) => alphabet,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    let words = match import::read(file, format, options, &alphabet) {
+        Ok(words) => words,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    println!("{} words read from {}", words.len(), file.display());
+
+    match dico::merge(dir, &words) {
+        Ok(added) => {
+            for (path, count) in added {
+                println!("{}: {} new words", path.display(), count);
+            }
+        }
+        Err(err) => eprintln!("{}", err),
+    }
+}
+
+fn check_dico(dir: &Path, letters: &[String]) {
+    let letters = if letters.is_empty() {
+        match dico::letters(dir) {
+            Ok(letters) => letters,
+            Err(err) => {
+                eprintln!("{}", err);
                 return;
             }
+        }
+    } else {
+        letters.to_vec()
+    };
+
+    let mut issues = 0;
+
+    for letter in letters {
+        let report = match dico::check(dir, &letter) {
+            Ok(report) => report,
+            Err(err) => {
+                eprintln!("{}", err);
+                continue;
+            }
         };
 
-        if dico.is_empty() {
-            eprintln!("No world of len {} found in '{}.txt'", mask_desc.len, mask_desc.dico);
+        let words: usize = report.lengths.values().sum();
+        println!("{}: {} words", dico::path(dir, &letter).display(), words);
+
+        for (len, count) in &report.lengths {
+            println!("  len {}: {}", len, count);
+        }
+
+        for issue in &report.issues {
+            println!("  {}", issue);
+        }
+
+        issues += report.issues.len();
+    }
+
+    if issues > 0 {
+        println!("{} issues found, 'dico fix' can fix them", issues);
+    }
+}
+
+fn fix_dico(dir: &Path) {
+    let report = match dico::fix(dir) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    for row in &report.dropped {
+        println!("Dropped: '{}'", row);
+    }
+
+    for (path, words) in &report.written {
+        println!("{} rewritten ({} words)", path.display(), words);
+    }
+
+    if report.dropped.is_empty() && report.written.is_empty() {
+        println!("Nothing to fix");
+    }
+}
+
+fn query(dir: &Path, spec: &str, format: output::Format) {
+    let alphabet = match Alphabet::load(dir) {
+        Ok(alphabet) => alphabet,
+        Err(err) => {
+            eprintln!("{}", err);
             return;
         }
+    };
 
-        if let Some(word) = &args.auto {
-            if !dico.contains(word) {
-                eprintln!("The word '{}' won't be found: not in the dictionary", word);
+    let mask = match mask::Mask::parse(spec, &alphabet) {
+        Ok(mask) => mask,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    let dico = match MaskDescriptor::of_mask(&mask, &alphabet).load(dir) {
+        Ok(dico) => dico,
+        Err(error) => {
+            eprintln!("{}", error);
+            return;
+        }
+    };
+
+    match mask.candidates(&dico.codes) {
+        Ok(words) => format.print(&output::Event::Matching {
+            count: words.len(),
+            words: words.iter().map(|&id| dico.words[id].as_str()).collect(),
+        }),
+        Err(err) => eprintln!("{}", err),
+    }
+}
+
+fn analyze(dir: &Path, rules: Rules, answer: &str, guesses: &[String], markdown: Option<&Path>, format: output::Format) {
+    let alphabet = match Alphabet::load(dir) {
+        Ok(alphabet) => alphabet,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    let session = match MaskDescriptor::of_word(answer, &alphabet, rules) {
+        Ok(desc) => match Session::new(desc, rules, alphabet) {
+            Ok(session) => session,
+            Err(err) => {
+                eprintln!("{}", err);
                 return;
             }
+        },
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
         }
+    };
 
-        let mut mask = mask::Mask::new(mask_desc.dico, mask_desc.len);
-        let mut result = mask::ResultState::new(mask_desc.len as usize + 1);
-        println!("Dico loaded in {}µs ({} words)", start.elapsed().as_micros(), dico.len());
+    let dico = match session.desc().load(dir) {
+        Ok(dico) => dico,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
 
-        while !result.complet() {
-            let start = std::time::Instant::now();
+    let id = match dico.position(answer) {
+        Some(id) => id,
+        None => {
+            eprintln!("The word '{}' can't be analyzed: not in the dictionary", answer);
+            return;
+        }
+    };
 
-            let (word_id, score) = match mask.find_best(&dico) {
-                Ok(stats) => stats,
-                Err(err) => {
-                    eprintln!("{}", err);
-                    return;
+    let word_len = dico.codes[id].len();
+
+    for guess in guesses {
+        match dico.alphabet.encode(guess) {
+            Ok(code) if code.len() == word_len => (),
+            Ok(_) => {
+                eprintln!("'{}' doesn't have {} letters", guess, word_len);
+                return;
+            }
+            Err(c) => {
+                eprintln!("Invalid word '{}': invalid char '{}'", guess, c);
+                return;
+            }
+        }
+    }
+
+    let report = match analyze::replay(session, &dico, id, guesses) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    format.print(&output::Event::Analysis(&report));
+
+    if let Some(path) = markdown {
+        match std::fs::write(path, report.markdown()) {
+            Ok(()) if format.is_json() => (),
+            Ok(()) => println!("Report written in {}", path.display()),
+            Err(err) => eprintln!("Unable to write the report in {}: {}", path.display(), err),
+        }
+    }
+}
+
+/// replay the turns and print the words remaining with the best word to play next,
+/// errors are returned so the process fails
+fn solve_turns(dir: &Path, session: &mut Session, turns: &[(String, mask::ResultState)], format: output::Format) -> Result<(), String> {
+    for (word, result) in turns {
+        session.play(word, result.clone()).map_err(|err| format!("{} {}: {}", word, result, err))?;
+    }
+
+    let dico = session.desc().load(dir).map_err(|err| err.to_string())?;
+
+    if session.complet() {
+        print_share(session, format);
+        return Ok(());
+    }
+
+    let step = session.next_step(&dico.codes).map_err(|err| err.to_string())?;
+    print_step(&step, &dico, format, true);
+
+    Ok(())
+}
+
+/// print the next step of the solver, with the words remaining before a suggestion if `remaining`
+fn print_step(step: &Step, dico: &dico::Dico, format: output::Format, remaining: bool) {
+    match step {
+        Step::NoWord => format.print(&output::Event::Remaining { count: 0, candidates: Vec::new() }),
+        Step::Obviously(id) => format.print(&output::Event::Obviously { word: &dico.words[*id] }),
+        Step::Suggestion { candidates, best, score, scores, seconds } => {
+            if remaining {
+                // the list is only printed in json
+                format.print(&output::Event::Remaining {
+                    count: candidates.len(),
+                    candidates: candidates.iter()
+                        .filter(|_| format.is_json())
+                        .map(|&id| dico.words[id].as_str())
+                        .collect(),
+                });
+            }
+
+            format.print(&output::Event::Suggestion {
+                word: &dico.words[*best],
+                score: *score,
+                seconds: *seconds,
+                ranking: mask::rank(scores)
+                    .into_iter()
+                    .take(RANKING)
+                    .map(|id| output::Scored { word: &dico.words[id], score: scores[id] })
+                    .collect(),
+            });
+        }
+    }
+}
+
+/// count of the best words given with a suggestion in json
+const RANKING: usize = 10;
+
+/// play the game until the word is found, saving every turn in `save` if provided
+/// guesses of the player are graded if `hint` is provided
+fn solve(mut session: Session, dir: &Path, auto: Option<&str>, save: Option<&Path>, mut hint: Option<&mut hint::Hint>, format: output::Format) {
+    let desc = session.desc();
+    let start = std::time::Instant::now();
+    let mut dir = dir.to_path_buf();
+    let mut dico = match desc.load(&dir) {
+        Ok(dico) => dico,
+        Err(error) => {
+            eprintln!("{}", error);
+            return;
+        }
+    };
+
+    if dico.is_empty() {
+        eprintln!("No world of len {} found in {}", desc.word_len(), desc.path(&dir).display());
+        return;
+    }
+
+    // the answer must have the revealed letters of the game, the results would contradict them otherwise
+    let fits = |word: &str| match session.alphabet().encode(word) {
+        Ok(code) if code.len() == session.desc().word_len() as usize => session.candidates(&[code]).is_ok_and(|ids| !ids.is_empty()),
+        _ => false,
+    };
+
+    let target = match auto.map(|word| (word, dico.position(word))) {
+        Some((word, _)) if !fits(word) => {
+            eprintln!("The word '{}' doesn't match the game {}", word, session.desc());
+            return;
+        }
+        Some((_, Some(id))) => Some(dico.codes[id].clone()),
+        Some((word, None)) => {
+            eprintln!("The word '{}' won't be found: not in the dictionary", word);
+            return;
+        }
+        None => None,
+    };
+
+    format.print(&output::Event::Loaded { words: dico.len(), lang: dico.alphabet.lang(), micros: start.elapsed().as_micros() });
+
+    for (word, result) in session.history() {
+        format.print(&output::Event::Replayed { word, result });
+    }
+
+    let mut check = !session.history().is_empty();
+    let color = board::enabled();
+
+    loop {
+        if check {
+            // println!("{:?}", session.mask());
+
+            if !format.is_json() {
+                print!("{}", board::render(&session, color));
+            }
+
+            // a word found out of the dico would leave no word
+            if session.complet() && session.history().last().is_some_and(|(word, _)| dico.position(word).is_none()) {
+                print_share(&session, format);
+
+                if !format.is_json() {
+                    add_missing(&session, &dico, &dir);
                 }
-            };
 
-            println!("Word found in {:.2}s", start.elapsed().as_secs_f32());
-            println!("Best word: {} ({:.2})", dico[word_id], score);
+                return;
+            }
+        }
 
-            if let Some(word) = &args.auto {
-                if let Err(err) = result.update_with(&dico[word_id], word) {
-                    eprintln!("{}", err);
-                    return;
+        let step = match session.next_step(&dico.codes) {
+            Ok(step) => step,
+            Err(err) => {
+                eprintln!("{}", err);
+                return;
+            }
+        };
+
+        // the player is asked what to do when no word is left, except in json
+        if format.is_json() || !matches!(step, Step::NoWord) {
+            print_step(&step, &dico, format, check);
+        }
+
+        let (scores, word_id) = match step {
+            Step::NoWord if format.is_json() => return,
+            Step::NoWord => {
+                if out_of_dico(&mut session, &mut dico, &mut dir, save) {
+                    continue;
                 }
 
-                println!("Result: {}", result);
+                add_missing(&session, &dico, &dir);
+                return;
+            }
+            Step::Obviously(id) => {
+                // the answer is known in auto mode, the player confirms it otherwise,
+                // so the game can be ended for the share text
+                let asked = target.is_none() && !format.is_json() && !session.complet();
+                let found = session.complet() || target.is_some() || (asked && confirm(&dico.words[id]));
+
+                if found && !session.complet() {
+                    let mut result = mask::ResultState::new(dico.codes[id].len(), false);
+                    let played = session.rules().feedback(&dico.codes[id], &dico.codes[id], &mut result)
+                        .and_then(|()| session.play(&dico.words[id], result));
 
-                if let Err(err) = mask.update(&dico[word_id], &result) {
-                    eprintln!("{}", err);
-                    return;
+                    match played {
+                        Ok(()) => save_session(&session, save),
+                        Err(err) => eprintln!("{}", err),
+                    }
+                }
+
+                print_share(&session, format);
+
+                if asked && !found {
+                    add_missing(&session, &dico, &dir);
                 }
-            } else {
-                let mut buf = String::with_capacity(10);
 
-                result = loop {
-                    buf.clear();
+                return;
+            }
+            Step::Suggestion { best, scores, .. } => (scores, best),
+        };
+
+        check = true;
+        let best = &dico.words[word_id];
+
+        if let Some(target) = &target {
+            let code = &dico.codes[word_id];
+            let mut result = mask::ResultState::new(code.len(), session.rules().reveal_first());
+
+            if let Err(err) = session.rules().feedback(code, target, &mut result) {
+                eprintln!("{}", err);
+                return;
+            }
+
+            format.print(&output::Event::Played { word: best, result: &result });
+
+            if let Err(err) = session.play(best, result) {
+                eprintln!("{}", err);
+                return;
+            }
+        } else {
+            let mut buf = String::with_capacity(10);
+
+            loop {
+                buf.clear();
+
+                // json lines stay valid without the prompt
+                if !format.is_json() {
                     print!("Result: ");
                     std::io::stdout().flush().unwrap();
+                }
 
-                    if let Err(err) = std::io::stdin().read_line(&mut buf) {
+                match std::io::stdin().read_line(&mut buf) {
+                    Ok(0) => return,
+                    Ok(_) => (),
+                    Err(err) => {
                         eprintln!("{}", err);
                         return;
                     }
+                }
+
+                // "<word> <result>" when another word than the best one was played
+                let input = buf.trim();
+                let (word, result) = match input.split_once(' ') {
+                    Some((word, result)) if mask::ResultState::try_from(word).is_err() => (word, result),
+                    _ => (best.as_str(), input),
+                };
+
+                let rs: mask::ResultState = match result.try_into() {
+                    Ok(rs) => rs,
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        continue;
+                    }
+                };
+
+                let grade = hint.as_deref_mut()
+                    .map(|hint| dico.position(word).and_then(|guess| hint.grade(&scores, guess)));
 
-                    let rs: mask::ResultState = match buf.trim().try_into() {
-                        Ok(rs) => rs,
-                        Err(err) => {
-                            eprintln!("{}", err);
-                            continue;
+                match session.play(word, rs.clone()) {
+                    Err(err) => eprintln!("{}", err),
+                    Ok(()) => {
+                        if format.is_json() {
+                            format.print(&output::Event::Played { word, result: &rs });
                         }
-                    };
 
-                    match mask.update(&dico[word_id], &rs) {
-                        Err(err) => eprintln!("{}", err),
-                        Ok(()) => break rs,
+                        match grade {
+                            Some(Some(grade)) => format.print(&grade.event(word, &dico.words[grade.best])),
+                            Some(None) => format.print(&output::Event::NotGraded { word }),
+                            None => (),
+                        }
+
+                        break;
                     }
                 }
-            };
+            }
+        }
+
+        save_session(&session, save);
+    }
+}
+
+fn print_summary(hint: Option<&hint::Hint>, format: output::Format) {
+    if let Some(summary) = hint.and_then(hint::Hint::summary) {
+        format.print(&summary);
+    }
+}
+
+/// once the word is found, print the results as colored squares
+fn print_share(session: &Session, format: output::Format) {
+    if session.complet() {
+        format.print(&output::Event::Share { text: session.share(None) });
+    }
+}
+
+fn save_session(session: &Session, save: Option<&Path>) {
+    if let Some(path) = save {
+        if let Err(err) = session.save(path) {
+            eprintln!("Unable to save the game in {}: {}", path.display(), err);
+        }
+    }
+}
+
+/// no word of the dico matches the results: let the user cancel a result
+/// or switch to another dictionary
+///
+/// return false if the game is over
+fn out_of_dico(session: &mut Session, dico: &mut dico::Dico, dir: &mut PathBuf, save: Option<&Path>) -> bool {
+    let desc = session.desc().clone();
+
+    println!("No word matches: the answer is not in {}", desc.path(dir).display());
+    println!("Type 'undo' to cancel the last result, 'dico <dir>' to load another dictionary or 'quit'");
+
+    let mut buf = String::with_capacity(16);
+
+    loop {
+        buf.clear();
+        print!("> ");
+        std::io::stdout().flush().unwrap();
+
+        match std::io::stdin().read_line(&mut buf) {
+            Ok(0) => return false,
+            Ok(_) => (),
+            Err(err) => {
+                eprintln!("{}", err);
+                return false;
+            }
+        }
 
-            // println!("{:?}", mask);
+        let input = buf.trim();
+        let (command, arg) = input.split_once(' ').unwrap_or((input, ""));
+        let arg = arg.trim();
 
-            match mask.filter(&dico) {
-                mask::FilterResult::Count(possibilities) => {
-                    println!("{} words remaining", possibilities);
+        match command {
+            "undo" => match session.undo() {
+                Some((word, result)) => {
+                    println!("Canceled: {} {}", word, result);
+                    save_session(session, save);
+                    return true;
                 }
-                mask::FilterResult::Word(word) => {
-                    println!("Obviously: {}", word);
-                    return;
+                None => eprintln!("Nothing to undo"),
+            },
+            "dico" => match desc.load(Path::new(arg)) {
+                Ok(new_dico) if new_dico.is_empty() => eprintln!("No world of len {} found in {}", desc.word_len(), arg),
+                Ok(new_dico) => {
+                    println!("Dico loaded ({} words)", new_dico.len());
+                    *dir = PathBuf::from(arg);
+                    *dico = new_dico;
+                    return true;
                 }
-                mask::FilterResult::Err(err) => {
+                Err(err) => eprintln!("{}", err),
+            },
+            "quit" => return false,
+            _ => eprintln!("Unknown command '{}'", command),
+        }
+    }
+}
+
+/// ask the player if the only word left was the answer
+fn confirm(word: &str) -> bool {
+    let mut buf = String::with_capacity(8);
+
+    print!("Was it {}? [Y/n] ", word);
+    std::io::stdout().flush().unwrap();
+
+    match std::io::stdin().read_line(&mut buf) {
+        Ok(0) => false,
+        Ok(_) => !matches!(buf.trim(), "n" | "no"),
+        Err(err) => {
+            eprintln!("{}", err);
+            false
+        }
+    }
+}
+
+/// at the end of a game, offer to add the answer when it's missing from the dictionary
+fn add_missing(session: &Session, dico: &dico::Dico, dir: &Path) {
+    let found = match session.history().last() {
+        Some((word, _)) if session.complet() => Some(word.as_str()),
+        _ => None,
+    };
+
+    if found.is_some_and(|word| dico.position(word).is_some()) {
+        return;
+    }
+
+    let mut buf = String::with_capacity(16);
+
+    loop {
+        buf.clear();
+
+        match found {
+            Some(word) => print!("'{}' is not in the dictionary, add it? [y/N] ", word),
+            None => print!("Answer to add to the dictionary (empty to skip): "),
+        }
+
+        std::io::stdout().flush().unwrap();
+
+        match std::io::stdin().read_line(&mut buf) {
+            Ok(0) => return,
+            Ok(_) => (),
+            Err(err) => {
+                eprintln!("{}", err);
+                return;
+            }
+        }
+
+        match (found, buf.trim()) {
+            (_, "") => return,
+            (Some(word), "y" | "yes") => {
+                add_answer(session, dir, word);
+                return;
+            }
+            (Some(_), _) => return,
+            (None, answer) => if add_answer(session, dir, answer) {
+                return;
+            },
+        }
+    }
+}
+
+/// add the answer of the game to the dictionary if it matches the results
+fn add_answer(session: &Session, dir: &Path, word: &str) -> bool {
+    let code = match session.alphabet().encode(word) {
+        Ok(code) => code,
+        Err(c) => {
+            eprintln!("Invalid word '{}': invalid char '{}'", word, c);
+            return false;
+        }
+    };
+
+    if code.len() != session.desc().word_len() as usize {
+        eprintln!("'{}' doesn't have {} letters", word, session.desc().word_len());
+        return false;
+    }
+
+    match session.candidates(&[code]) {
+        Ok(words) if words.is_empty() => {
+            eprintln!("'{}' doesn't match the results", word);
+            false
+        }
+        Ok(_) => add_word(dir, word),
+        Err(err) => {
+            eprintln!("{}", err);
+            false
+        }
+    }
+}
+
+fn add_word(dir: &Path, word: &str) -> bool {
+    match dico::add(dir, word) {
+        Ok(true) => {
+            println!("'{}' added to the dictionaries of {}", word, dir.display());
+            true
+        }
+        Ok(false) => {
+            println!("'{}' is already in the dictionary", word);
+            true
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            false
+        }
+    }
+}
+
+// use https://www.tusmo.xyz/s3da53bb 4 tests
+// inventees
+
+fn main() {
+    let mut args = Args::from_args();
+
+    if let Some(command) = &args.command {
+        // scripts asking for json must not get text they can't parse
+        if let Some(name) = command.text_only().filter(|_| args.format.is_json()) {
+            eprintln!("--format json isn't supported by the {} command", name);
+            std::process::exit(1);
+        }
+
+        match command {
+            Command::Query { spec } => query(&args.dico_dir, spec, args.format),
+            Command::Solve { search, turns } => {
+                let solved = Alphabet::load(&args.dico_dir)
+                    .map_err(|err| err.to_string())
+                    .and_then(|alphabet| Session::new(search.clone(), args.rules, alphabet).map_err(|err| err.to_string()))
+                    .and_then(|mut session| solve_turns(&args.dico_dir, &mut session, turns, args.format));
+
+                // scripts rely on the status to know if the turns were understood
+                if let Err(err) = solved {
                     eprintln!("{}", err);
-                    return;
+                    std::process::exit(1);
+                }
+            }
+            Command::Resume { file } => match Alphabet::load(&args.dico_dir) {
+                Ok(alphabet) => match Session::load(file, alphabet) {
+                    Ok(session) if args.tui => tui::run(session, &args.dico_dir, Some(file)),
+                    Ok(session) => {
+                        let mut hint = args.hint.then(hint::Hint::default);
+                        solve(session, &args.dico_dir, None, Some(file), hint.as_mut(), args.format);
+                        print_summary(hint.as_ref(), args.format);
+                    }
+                    Err(err) => eprintln!("{}", err),
+                },
+                Err(err) => eprintln!("{}", err),
+            },
+            Command::Play { search, tries, seed } => {
+                let options = play::Options {
+                    tries: *tries,
+                    seed: seed.unwrap_or_else(random::seed),
+                    hint: args.hint,
+                    title: None,
+                };
+
+                play::run(&args.dico_dir, args.rules, search.clone(), &options);
+            }
+            Command::Duel { search, players, ai, tries, rounds, seed } => {
+                let mut names = players.clone();
+
+                if names.is_empty() {
+                    names = match ai {
+                        Some(_) => vec!["Player".to_string()],
+                        None => vec!["Player 1".to_string(), "Player 2".to_string()],
+                    };
+                }
+
+                let mut players: Vec<duel::Player> = names.into_iter()
+                    .map(|name| duel::Player { name, ai: None, score: 0 })
+                    .collect();
+
+                if let Some(strength) = ai {
+                    players.push(duel::Player { name: format!("Solver ({})", strength), ai: Some(*strength), score: 0 });
+                }
+
+                if players.len() < 2 {
+                    eprintln!("A duel needs two players at least, add another --player or --ai");
+                } else {
+                    let options = duel::Options {
+                        tries: *tries,
+                        rounds: *rounds,
+                        seed: seed.unwrap_or_else(random::seed),
+                    };
+
+                    duel::run(&args.dico_dir, args.rules, search.clone(), players, &options);
+                }
+            }
+            Command::Evil { search, ai, tries, seed } => {
+                evil::run(&args.dico_dir, args.rules, search.clone(), *ai, *tries, seed.unwrap_or_else(random::seed));
+            }
+            Command::Daily { search, date, seed, tries, solve } => {
+                let date = date.unwrap_or_else(daily::Date::today);
+                let options = play::Options {
+                    tries: *tries,
+                    seed: date.seed(*seed),
+                    hint: args.hint,
+                    title: Some(format!("{} daily {}", args.rules, date)),
+                };
+
+                if *solve {
+                    daily::solve(&args.dico_dir, args.rules, search.clone(), &options);
+                } else {
+                    play::run(&args.dico_dir, args.rules, search.clone(), &options);
                 }
             }
+            Command::Analyze { answer, guesses, markdown } => {
+                analyze(&args.dico_dir, args.rules, answer, guesses, markdown.as_deref(), args.format);
+            }
+            Command::Serve { stdio, http } => match http {
+                Some(address) if !*stdio => serve::http(&args.dico_dir, address),
+                None if *stdio => serve::stdio(&args.dico_dir),
+                _ => eprintln!("Give one way to serve the requests: --stdio or --http <address>"),
+            },
+            Command::Dico(DicoCommand::Add { word }) => {
+                add_word(&args.dico_dir, word);
+            }
+            Command::Dico(DicoCommand::Check { letters }) => check_dico(&args.dico_dir, letters),
+            Command::Dico(DicoCommand::Fix) => fix_dico(&args.dico_dir),
+            Command::Dico(DicoCommand::Import { file, format, min_len, max_len, word_column, freq_column, min_freq }) => {
+                let options = import::Options {
+                    min_len: *min_len,
+                    max_len: *max_len,
+                    word_column: word_column.clone(),
+                    freq_column: freq_column.clone(),
+                    min_freq: *min_freq,
+                };
+
+                import_dico(&args.dico_dir, file, *format, &options);
+            }
+        }
+
+        return;
+    }
+
+    let alphabet = match Alphabet::load(&args.dico_dir) {
+        Ok(alphabet) => alphabet,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    if let Some(word) = &args.auto {
+        match MaskDescriptor::of_word(word, &alphabet, args.rules) {
+            // a given descriptor is kept, the word is checked against it by solve
+            Ok(_) if args.search.is_some() => (),
+            Ok(desc) => args.search = Some(desc),
+            Err(err) => {
+                eprintln!("{}", err);
+                args.search = None;
+                args.bench = None;
+            }
+        }
+    }
+
+    // wordle games don't need any descriptor
+    if !args.rules.reveal_first() && args.search.is_none() && args.auto.is_none() && args.bench.is_none() {
+        args.search = Some(MaskDescriptor { dico: None, len: 5, pattern: None });
+    }
+
+    if let Some(mask_desc) = args.search {
+        match Session::new(mask_desc, args.rules, alphabet) {
+            Ok(session) if args.tui && args.auto.is_none() => tui::run(session, &args.dico_dir, args.save.as_deref()),
+            Ok(session) => {
+                let mut hint = args.hint.then(hint::Hint::default);
+                solve(session, &args.dico_dir, args.auto.as_deref(), args.save.as_deref(), hint.as_mut(), args.format);
+                print_summary(hint.as_ref(), args.format);
+            }
+            Err(err) => eprintln!("{}", err),
         }
-    } else if let Some(char) = args.bench {
+    } else if let Some(letter) = args.bench {
+        // dictionaries are benched by first letter, it must be revealed
+        if !args.rules.reveal_first() {
+            eprintln!("--bench needs rules revealing the first letter, not {}", args.rules);
+            return;
+        }
+
         for word_len in 6..=9 {
             let start = std::time::Instant::now();
-            let dico = match dico::load(char, word_len) {
+            let desc = MaskDescriptor { dico: Some(letter.clone()), len: word_len - 1, pattern: None };
+
+            let session = match Session::new(desc, args.rules, alphabet.clone()) {
+                Ok(session) => session,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return;
+                }
+            };
+
+            let dico = match session.desc().load(&args.dico_dir) {
                 Ok(dico) => dico,
                 Err(error) => {
                     eprintln!("{}", error);
@@ -170,8 +1185,8 @@ fn main() {
                 }
             };
 
-            let mask = mask::Mask::new(char, word_len - 1);
-            let (word_id, _) = match mask.find_best(&dico) {
+            let best = session.scores(&dico.codes).and_then(|scores| mask::best(&scores).ok_or(mask::MaskError::NoCandidate));
+            let (word_id, _) = match best {
                 Ok(id) => id,
                 Err(err) => {
                     eprintln!("{}", err);
@@ -179,9 +1194,61 @@ fn main() {
                 }
             };
 
-            println!("For words of len {}, best word is {} ({}) in {:.2}s", word_len, dico[word_id], word_id, start.elapsed().as_secs_f32());
+            args.format.print(&output::Event::Bench {
+                len: word_len,
+                word: &dico.words[word_id],
+                index: word_id,
+                seconds: start.elapsed().as_secs_f32(),
+            });
         }
     } else if let Err(err) = Args::clap().print_help() {
         eprintln!("{}", err);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_turns() {
+        use super::parse_turn;
+
+        let (word, result) = parse_turn("Brulions:rybyybbb").unwrap();
+        assert_eq!((word.as_str(), result.to_string().as_str()), ("brulions", "rybyybbb"));
+
+        assert!(parse_turn("brulions").is_err());
+        assert!(parse_turn("brulions:rxb").is_err());
+    }
+
+    #[test]
+    fn solve_readme() {
+        use super::{parse_turn, Alphabet, MaskDescriptor, Rules, Session, Step};
+        use std::path::Path;
+
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("dico");
+        let desc: MaskDescriptor = "b7".parse().unwrap();
+        let alphabet = Alphabet::load(&dir).unwrap_or_else(|err| panic!("{}", err));
+        let mut session = Session::new(desc, Rules::default(), alphabet).unwrap_or_else(|err| panic!("{}", err));
+        let dico = session.desc().load(&dir).unwrap_or_else(|err| panic!("{}", err));
+
+        // solve b7 brulions:rybyybbb
+        let (word, result) = parse_turn("brulions:rybyybbb").unwrap();
+        session.play(&word, result).unwrap_or_else(|err| panic!("{}", err));
+
+        match session.next_step(&dico.codes) {
+            Ok(Step::Suggestion { candidates, best, .. }) => {
+                assert_eq!(candidates.len(), 7);
+                assert_eq!(dico.words[best], "babiller");
+            }
+            _ => panic!("a suggestion was expected"),
+        }
+
+        // solve b7 brulions:rybyybbb babiller:rybyybby
+        let (word, result) = parse_turn("babiller:rybyybby").unwrap();
+        session.play(&word, result).unwrap_or_else(|err| panic!("{}", err));
+        assert!(matches!(session.next_step(&dico.codes), Ok(Step::Obviously(id)) if dico.words[id] == "blizzard"));
+
+        // the result of a turn must have the length of the words
+        let (word, result) = parse_turn("brulions:rybyyb").unwrap();
+        assert!(session.play(&word, result).is_err());
+    }
+}
@@ -1,42 +1,56 @@
 // use std::io::Write as flush;
 use std::fmt::Write;
-use rayon::iter::{
-    IntoParallelRefIterator,
-    IndexedParallelIterator,
-    ParallelIterator
-};
 
-const A_USIZE: usize = b'a' as usize;
+use crate::alphabet::{Alphabet, MAX_LETTERS};
 
+/// words are given as the index of their letters in the alphabet, see `Alphabet::encode`
 #[derive(Clone)]
 pub struct Mask {
     mask: Vec<LetterMask>,
-    count: [u8; 26],
+    count: [u8; MAX_LETTERS],
 }
 
 impl Mask {
-    /// len doesn't count start char !
-    pub fn new(start: char, len: u8) -> Self {
-        let mut mask = vec![LetterMask::default(); len as usize + 1];
-        mask[0].set(start as u8);
+    /// len doesn't count start char, if the rules reveal one !
+    pub fn new(alphabet: &Alphabet, start: Option<u8>, len: u8) -> Self {
+        let mut mask = vec![LetterMask::full(alphabet); len as usize + start.is_some() as usize];
 
-        Self { mask, count: [0; 26] }
+        if let Some(start) = start {
+            mask[0].set(start);
+        }
+
+        Self { mask, count: [0; MAX_LETTERS] }
+    }
+
+    /// first letter of the mask, if known
+    pub fn first(&self) -> Option<u8> {
+        self.mask[0].red_char()
+    }
+
+    /// letter at each place, if known
+    pub fn known(&self) -> Vec<Option<u8>> {
+        self.mask.iter().map(LetterMask::red_char).collect()
+    }
+
+    /// len of matching words, start char included
+    pub fn word_len(&self) -> usize {
+        self.mask.len()
     }
 
     #[inline(always)]
-    fn match_with(&self, word: &str) -> Result<bool, MaskError> {
+    fn match_with(&self, word: &[u8]) -> Result<bool, MaskError> {
         if self.mask.len() == word.len() {
             let mask_match = self.mask
-                .iter().zip(word.as_bytes())
+                .iter().zip(word)
                 .all(|(mask, &char)| mask.match_with(char));
 
             if !mask_match {
                 return Ok(false);
             }
 
-            let mut count = [0; 26];
-            for &c in word.as_bytes() {
-                count[c as usize - A_USIZE] += 1;
+            let mut count = [0; MAX_LETTERS];
+            for &c in word {
+                count[c as usize] += 1;
             }
 
             for (&sc, c) in self.count.iter().zip(count) {
@@ -59,12 +73,12 @@ impl Mask {
     }
 
     #[inline(always)]
-    pub fn update(&mut self, word: &str, result: &ResultState) -> Result<(), MaskError> {
+    pub fn update(&mut self, word: &[u8], result: &ResultState) -> Result<(), MaskError> {
         if self.mask.len() == word.len() && self.mask.len() == result.state.len() {
             // update count
-            let mut stats = [(0, 0); 26];
-            for (&c, &rc) in word.as_bytes().iter().zip(&result.state) {
-                let index = c as usize - A_USIZE;
+            let mut stats = [(0, 0); MAX_LETTERS];
+            for (&c, &rc) in word.iter().zip(&result.state) {
+                let index = c as usize;
                 stats[index].0 += 1;
 
                 if rc != ResultColor::Blue {
@@ -84,7 +98,7 @@ impl Mask {
 
             // update mask
             // red
-            let iterator = word.as_bytes().iter()
+            let iterator = word.iter()
                 .enumerate()
                 .zip(&result.state)
                 .filter_map(|(data, &rs)|
@@ -96,13 +110,13 @@ impl Mask {
             }
 
             // yellow
-            let mut count = [0; 26];
+            let mut count = [0; MAX_LETTERS];
             for c in self.mask.iter().filter_map(|lm| lm.red_char()) {
-                count[c as usize - A_USIZE] += 1;
+                count[c as usize] += 1;
             }
 
             for ((i, &sc), c) in self.count.iter().enumerate().zip(count) {
-                let char = i as u8 + b'a';
+                let char = i as u8;
                 let exact = sc & 0b1000_0000 != 0;
                 let sc = sc & 0b0111_1111;
 
@@ -111,7 +125,7 @@ impl Mask {
                         lm.remove(char);
                     }
                 } else {
-                    word.as_bytes().iter()
+                    word.iter()
                         .zip(&mut self.mask)
                         .filter(|(&c, _)| c == char)
                         .for_each(|(_, mask)| mask.remove(char));
@@ -124,165 +138,228 @@ impl Mask {
         }
     }
 
-    #[inline(always)]
-    pub fn find_best(&self, dico: &[String]) -> Result<(usize, f32), MaskError> {
-        let mut valid_target = Vec::with_capacity(dico.len());
-
-        // update valid target
-        for target in dico.iter() {
-            valid_target.push(self.match_with(target)?);
-        }
-
-        let best_progress = dico.par_iter().enumerate().fold(|| Ok((0, f32::MAX)), |best, (idx, word)| {
-            let best_progress = match best {
-                Ok(best) => best,
-                _ => return best,
-            };
-
-            let mut res = ResultState::new(self.mask.len());
-            let mut self_clone = self.clone();
-
-            let mut states = [0; 3_usize.pow(8)];
-            let mut matchs = 0.0;
-            let mut sum = 0.0;
-
-            let iterator = dico.iter().zip(&valid_target)
-                .filter_map(|(target, &valid)| if valid { Some(target) } else { None });
-
-            for target in iterator {
-                res.update_with(word, target)?;
-                let state_id = res.state_id();
-
-                if states[state_id] > 0 {
-                    sum += states[state_id] as f32;
-                    matchs += 1.0;
-                    continue;
-                }
-
-                self_clone.update(word, &res)?;
-
-                match self_clone.filter(dico) {
-                    FilterResult::Err(err) => return Err(err),
-                    FilterResult::Count(score) => {
-                        states[state_id] = score as u8;
-                        sum += score as f32;
-                        matchs += 1.0;
-                    },
-                    FilterResult::Word(_) => if res.complet() {
-                        states[state_id] = 1;
-                        matchs += 1.0;
-                        sum += 1.0;
-                    },
-                }
-
-                self_clone.revert_from(self);
-            }
-
-            let avg = sum / matchs;
-            if avg < best_progress.1 {
-                Ok((idx, avg))
-            } else {
-                Ok(best_progress)
-            }
-
-            // \x1B[1K clear the line \x1b[1G place the cursor in the first col
-            // print!("\x1B[1K\rBest: {} ({:.2}) | current: {word} ({avg:.2})", dico[best_progress.0], best_progress.1);
-            // std::io::stdout().flush().unwrap();
-        }).reduce_with(|lhs, rhs| {
-            match (lhs, rhs) {
-                (Ok(lhs), Ok(rhs)) => {
-                    if lhs.1 <= rhs.1 {
-                        Ok(lhs)
-                    } else {
-                        Ok(rhs)
-                    }
-                }
-                (Err(e), _) | (_, Err(e)) => Err(e)
-            }
-        }).unwrap();
-
-        // println!();
-        best_progress
-    }
-
-    #[inline(always)]
-    pub fn filter<'a>(&self, dico: &'a[String]) -> FilterResult<'a> {
-        let mut last_match = 0;
-        let mut count = 0;
+    /// id of all words of the dico still matching the mask
+    pub fn candidates(&self, dico: &[Vec<u8>]) -> Result<Vec<usize>, MaskError> {
+        let mut candidates = Vec::new();
 
         for (id, word) in dico.iter().enumerate() {
-            match self.match_with(word) {
-                Err(err) => return FilterResult::Err(err),
-                Ok(match_with) => if match_with {
-                    last_match = id;
-                    count += 1;
-                }
+            if self.match_with(word)? {
+                candidates.push(id);
             }
         }
 
-        if count == 1 {
-            FilterResult::Word(&dico[last_match])
-        } else {
-            FilterResult::Count(count)
-        }
+        Ok(candidates)
     }
 
-    #[inline(always)]
-    fn revert_from(&mut self, rhs: &Mask) {
-        self.count = rhs.count;
-        self.mask
-            .iter_mut().zip(&rhs.mask)
-            .for_each(|(lhs, rhs)| *lhs = *rhs);
+    /// first letter of word where result disagrees with the known letters
+    pub fn conflict(&self, word: &[u8], result: &ResultState) -> Option<u8> {
+        self.mask.iter()
+            .zip(word)
+            .zip(&result.state)
+            .find(|((lm, &c), &rc)| match rc {
+                ResultColor::Red => !lm.match_with(c),
+                _ => lm.red_char() == Some(c),
+            })
+            .map(|((_, &c), _)| c)
     }
 }
 
+/// the mask doesn't know its alphabet: letters are named as in the default one,
+/// and by their index past its last letter
 impl std::fmt::Debug for Mask {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let alphabet = Alphabet::default();
+        let size = alphabet.size() as u8;
+        let name = |c: u8| if c < size { alphabet.letter(c).to_string() } else { format!("#{}", c) };
+
         for (i, lm) in self.mask.iter().enumerate() {
             write!(f, "{}:", i)?;
 
-            for c in b'a'..=b'z' {
+            for c in 0..size {
                 if lm.match_with(c) {
-                    f.write_char(c as char)?;
+                    f.write_str(alphabet.letter(c))?;
                 } else {
                     f.write_char(' ')?;
                 }
             }
 
+            for c in (size..MAX_LETTERS as u8).filter(|&c| lm.match_with(c)) {
+                write!(f, " {}", name(c))?;
+            }
+
             f.write_char('\n')?;
         }
 
-        for (i, &c) in self.count.iter().enumerate() {
+        // letters past the default alphabet are only given when something is known
+        for (i, &c) in self.count.iter().enumerate().filter(|&(i, &c)| i < size as usize || c != 0) {
             let exact = match c & 0b1000_0000 {
                 0b0000_0000 => '+',
                 0b1000_0000 => '!',
                 _ => unreachable!(),
             };
             let count = c & 0b0111_1111;
-            let char = (i as u8 + b'a') as char;
 
-            write!(f, "{}:{}{} ", char, count, exact)?;
+            write!(f, "{}:{}{} ", name(i as u8), count, exact)?;
         }
 
         Ok(())
     }
 }
 
-pub enum FilterResult<'a> {
-    Count(usize),
-    Word(&'a str),
-    Err(MaskError),
+impl Mask {
+    /// build a mask from a constraint spec like `b..ll... +e -xyz 3^ae`:
+    /// - pattern: known letters at their place, '.' for unknown ones
+    /// - `+letters`: letters somewhere else in the word, repeat a letter to count it twice
+    /// - `-letters`: letters without any other occurrence
+    /// - `<pos>^letters`: letters not at position `pos` (starting at 1)
+    pub fn parse(spec: &str, alphabet: &Alphabet) -> Result<Self, PatternError> {
+        let mut tokens = spec.split_whitespace();
+        let pattern = tokens.next().ok_or(PatternError::Empty)?;
+
+        let mut mask = Vec::with_capacity(pattern.len());
+        let mut count = [0; MAX_LETTERS];
+
+        for letter in parse_letters(pattern, alphabet, true)? {
+            let mut lm = LetterMask::full(alphabet);
+
+            if let Some(letter) = letter {
+                lm.set(letter);
+                count[letter as usize] += 1;
+            }
+
+            mask.push(lm);
+        }
+
+        let mut exact = [false; MAX_LETTERS];
+
+        for token in tokens {
+            if let Some(letters) = token.strip_prefix('+') {
+                for letter in parse_letters(letters, alphabet, false)?.into_iter().flatten() {
+                    count[letter as usize] += 1;
+                }
+            } else if let Some(letters) = token.strip_prefix('-') {
+                for letter in parse_letters(letters, alphabet, false)?.into_iter().flatten() {
+                    exact[letter as usize] = true;
+                }
+            } else if let Some((pos, letters)) = token.split_once('^') {
+                let letters = parse_letters(letters, alphabet, false)?;
+                let lm = pos.parse::<usize>().ok()
+                    .and_then(|pos| pos.checked_sub(1))
+                    .and_then(|pos| mask.get_mut(pos))
+                    .ok_or_else(|| PatternError::InvalidPosition(pos.to_string()))?;
+
+                for letter in letters.into_iter().flatten() {
+                    lm.remove(letter);
+                }
+            } else {
+                return Err(PatternError::UnknownToken(token.to_string()));
+            }
+        }
+
+        let mut red = [0; MAX_LETTERS];
+        for c in mask.iter().filter_map(|lm| lm.red_char()) {
+            red[c as usize] += 1;
+        }
+
+        for (i, (sc, exact)) in count.iter_mut().zip(exact).enumerate() {
+            if exact {
+                if *sc == red[i] {
+                    mask.iter_mut().for_each(|lm| lm.remove(i as u8));
+                }
+
+                *sc |= 0b1000_0000;
+            }
+        }
+
+        Ok(Self { mask, count })
+    }
+}
+
+/// index of each letter, None for '.' if allowed
+fn parse_letters(letters: &str, alphabet: &Alphabet, unknown: bool) -> Result<Vec<Option<u8>>, PatternError> {
+    let mut parsed = Vec::with_capacity(letters.len());
+    let mut rest = letters;
+
+    while let Some(c) = rest.chars().next() {
+        if unknown && c == '.' {
+            parsed.push(None);
+            rest = &rest[1..];
+        } else {
+            let (index, len) = alphabet.next_letter(rest).ok_or(PatternError::InvalidChar(c))?;
+            parsed.push(Some(index));
+            rest = &rest[len..];
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// id and score of the first word with the lowest score
+pub fn best(scores: &[f32]) -> Option<(usize, f32)> {
+    let mut best: Option<(usize, f32)> = None;
+
+    for (id, &score) in scores.iter().enumerate() {
+        if !score.is_nan() && best.is_none_or(|(_, best)| score < best) {
+            best = Some((id, score));
+        }
+    }
+
+    best
+}
+
+/// ids of the words from the best to the worst, NaN scores last
+pub fn rank(scores: &[f32]) -> Vec<usize> {
+    let mut ids: Vec<usize> = (0..scores.len()).collect();
+
+    ids.sort_by(|&lhs, &rhs| {
+        let (lhs, rhs) = (scores[lhs], scores[rhs]);
+        lhs.partial_cmp(&rhs).unwrap_or_else(|| lhs.is_nan().cmp(&rhs.is_nan()))
+    });
+
+    ids
 }
 
 #[derive(Debug)]
 pub enum MaskError {
     IncompatibleLen(&'static str),
+    InvalidChar(char),
+    NoCandidate,
+    /// the result can't be true whatever the word is
+    Inconsistent(String),
+    /// the result contradicts a previous turn
+    Contradiction(usize, String),
+    /// the result contradicts the letters revealed before the first turn
+    Revealed(String),
 }
 
 impl std::fmt::Display for MaskError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             MaskError::IncompatibleLen(fnc) => write!(f, "{}: Operand have incompatible length", fnc),
+            MaskError::InvalidChar(c) => write!(f, "found invalid char in word: '{}'", c),
+            MaskError::NoCandidate => write!(f, "No word of the dictionary matches the mask"),
+            MaskError::Inconsistent(c) => write!(f, "Result is inconsistent on letter '{}': a yellow can't follow a blue of the same letter", c),
+            MaskError::Revealed(c) => write!(f, "Result contradicts the revealed letters on '{}'", c),
+            MaskError::Contradiction(turn, c) => write!(f, "Result contradicts turn {} on letter '{}'", turn, c),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PatternError {
+    Empty,
+    InvalidChar(char),
+    InvalidPosition(String),
+    UnknownToken(String),
+}
+
+impl std::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternError::Empty => write!(f, "pattern is empty"),
+            PatternError::InvalidChar(c) => write!(f, "found invalid char in pattern: '{}'", c),
+            PatternError::InvalidPosition(pos) => write!(f, "invalid position in pattern: '{}'", pos),
+            PatternError::UnknownToken(token) => write!(f, "unknown token in pattern: '{}'\n  tokens must look like '+abc', '-abc' or '3^abc'", token),
         }
     }
 }
@@ -311,7 +388,7 @@ impl LetterMask {
     #[inline(always)]
     fn red_char(&self) -> Option<u8> {
         if self.0.count_ones() == 1 {
-            Some(b'a' + self.0.trailing_zeros() as u8)
+            Some(self.0.trailing_zeros() as u8)
         } else {
             None
         }
@@ -319,14 +396,12 @@ impl LetterMask {
 
     #[inline(always)]
     fn mask(char: u8) -> u32 {
-        1 << (char - b'a')
+        1 << char
     }
-}
 
-impl std::default::Default for LetterMask {
-    fn default() -> Self {
-        // 26 bits set ot 1
-        Self(0x03_FF_FF_FF)
+    /// one bit set to 1 for each letter of the alphabet
+    fn full(alphabet: &Alphabet) -> Self {
+        Self(u32::MAX >> (MAX_LETTERS - alphabet.size()))
     }
 }
 
@@ -336,30 +411,34 @@ pub struct ResultState {
 }
 
 impl ResultState {
-    pub fn new(len: usize) -> Self {
+    /// the first letter is red if it has been revealed
+    pub fn new(len: usize, revealed: bool) -> Self {
         let mut state = vec![ResultColor::default(); len];
-        state[0] = ResultColor::Red;
+
+        if revealed {
+            state[0] = ResultColor::Red;
+        }
+
         Self { state }
     }
 
-    pub fn update_with(&mut self, guess: &str, target: &str) -> Result<(), MaskError> {
+    pub fn update_with(&mut self, guess: &[u8], target: &[u8]) -> Result<(), MaskError> {
         if self.state.len() == guess.len() && self.state.len() == target.len() {
             self.state.iter_mut().for_each(|rc| *rc = ResultColor::Blue);
             let mut used = 0_u16;
 
             // update red cells
-            guess.as_bytes()
-                .iter()
+            guess.iter()
                 .enumerate()
-                .zip(target.as_bytes())
+                .zip(target)
                 .for_each(|((i, &g), &t)| if g == t {
                     self.state[i] = ResultColor::Red;
                     used |= 1 << i;
                 });
 
             // update possible yellow cells
-            'guess: for (ig, &g) in guess.as_bytes().iter().enumerate() {
-                'target: for (it, &t) in target.as_bytes().iter().enumerate() {
+            'guess: for (ig, &g) in guess.iter().enumerate() {
+                'target: for (it, &t) in target.iter().enumerate() {
                     let mask = 1 << it;
 
                     if used & mask != 0 {
@@ -381,7 +460,8 @@ impl ResultState {
         }
     }
 
-    fn state_id(&self) -> usize {
+    /// unique id of the colors, lower than 3^len
+    pub fn state_id(&self) -> usize {
         self.state.iter().fold(0, |state, rc| {
             state * 3 + match rc {
                 ResultColor::Red => 0,
@@ -394,15 +474,97 @@ impl ResultState {
     pub fn complet(&self) -> bool {
         self.state.iter().all(|&rc| rc == ResultColor::Red)
     }
+
+    /// len of the graded word
+    pub fn word_len(&self) -> usize {
+        self.state.len()
+    }
+
+    pub fn colors(&self) -> &[ResultColor] {
+        &self.state
+    }
+
+    /// forget the places of the colors: reds first, then yellows, then blues
+    pub fn sort(&mut self) {
+        self.state.sort_by_key(|rc| match rc {
+            ResultColor::Red => 0,
+            ResultColor::Yellow => 1,
+            ResultColor::Blue => 2,
+        });
+    }
+
+    /// yellows are given from left to right, so a yellow can't follow a blue of the same letter
+    pub fn check(&self, word: &[u8]) -> Option<u8> {
+        let mut blue = [false; MAX_LETTERS];
+
+        for (&c, &rc) in word.iter().zip(&self.state) {
+            let index = c as usize;
+
+            match rc {
+                ResultColor::Blue => blue[index] = true,
+                ResultColor::Yellow if blue[index] => return Some(c),
+                _ => (),
+            }
+        }
+
+        None
+    }
+
+    /// first letter on which this result for word and the other one can't be both true
+    pub fn conflict(&self, word: &[u8], other: &ResultState, other_word: &[u8]) -> Option<u8> {
+        let cells = word.iter().zip(&self.state)
+            .zip(other_word.iter().zip(&other.state));
+
+        for ((&c, &rc), (&oc, &orc)) in cells {
+            let red_moved = match (rc, orc) {
+                (ResultColor::Red, ResultColor::Red) => c != oc,
+                (ResultColor::Red, _) | (_, ResultColor::Red) => c == oc,
+                _ => false,
+            };
+
+            if red_moved {
+                return Some(c);
+            }
+        }
+
+        let counts = Self::letter_counts(word, &self.state);
+        let other_counts = Self::letter_counts(other_word, &other.state);
+
+        for (i, ((count, exact), (other_count, other_exact))) in counts.into_iter().zip(other_counts).enumerate() {
+            if (exact && other_count > count) || (other_exact && count > other_count) {
+                return Some(i as u8);
+            }
+        }
+
+        None
+    }
+
+    /// minimal count of each letter, and if this count is exact
+    fn letter_counts(word: &[u8], state: &[ResultColor]) -> [(u8, bool); MAX_LETTERS] {
+        let mut counts = [(0, false); MAX_LETTERS];
+
+        for (&c, &rc) in word.iter().zip(state) {
+            let count = &mut counts[c as usize];
+
+            if rc == ResultColor::Blue {
+                count.1 = true;
+            } else {
+                count.0 += 1;
+            }
+        }
+
+        counts
+    }
 }
 
 impl std::convert::TryFrom<&str> for ResultState {
     type Error = ConvertError;
 
+    /// letters or colored squares, spaces and emoji variation selectors are ignored
     fn try_from(mask: &str) -> Result<Self, Self::Error> {
         let mut state = Vec::with_capacity(mask.len());
 
-        for c in mask.chars() {
+        for c in mask.chars().filter(|&c| c != '\u{fe0f}' && !c.is_whitespace()) {
             state.push(c.try_into()?)
         }
 
@@ -410,6 +572,34 @@ impl std::convert::TryFrom<&str> for ResultState {
     }
 }
 
+/// squares used by the share text of the sites
+#[derive(Clone, Copy)]
+pub enum Palette {
+    /// red, yellow circle and blue
+    Tusmo,
+    /// green, yellow and black
+    Wordle,
+}
+
+impl ResultState {
+    pub fn squares(&self, palette: Palette) -> String {
+        self.state.iter().map(|rc| match (palette, rc) {
+            (Palette::Tusmo, ResultColor::Red) => '🟥',
+            (Palette::Tusmo, ResultColor::Yellow) => '🟡',
+            (Palette::Tusmo, ResultColor::Blue) => '🟦',
+            (Palette::Wordle, ResultColor::Red) => '🟩',
+            (Palette::Wordle, ResultColor::Yellow) => '🟨',
+            (Palette::Wordle, ResultColor::Blue) => '⬛',
+        }).collect()
+    }
+}
+
+impl std::convert::From<Vec<ResultColor>> for ResultState {
+    fn from(state: Vec<ResultColor>) -> Self {
+        Self { state }
+    }
+}
+
 impl std::fmt::Display for ResultState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str: String = self.state.iter().map(|rc| -> char { (*rc).into() }).collect();
@@ -417,6 +607,13 @@ impl std::fmt::Display for ResultState {
     }
 }
 
+/// serialized as its text, like "rybbb"
+impl serde::Serialize for ResultState {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 impl std::fmt::Debug for ResultState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "ResultState(\"{}\")", self)
@@ -424,12 +621,13 @@ impl std::fmt::Debug for ResultState {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
-enum ResultColor {
+pub enum ResultColor {
     Blue,
     Yellow,
     Red,
 }
 
+#[allow(clippy::derivable_impls)]
 impl std::default::Default for ResultColor {
     fn default() -> Self {
         Self::Blue
@@ -451,9 +649,9 @@ impl std::convert::TryFrom<char> for ResultColor {
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
-            'r' | 'R' => Ok(ResultColor::Red),
-            'y' | 'Y' => Ok(ResultColor::Yellow),
-            'b' | 'B' => Ok(ResultColor::Blue),
+            'r' | 'R' | '🟥' | '🟩' => Ok(ResultColor::Red),
+            'y' | 'Y' | '🟨' | '🟡' => Ok(ResultColor::Yellow),
+            'b' | 'B' | '🟦' | '⬛' | '⬜' => Ok(ResultColor::Blue),
             c => Err(ConvertError::InvalidChar(c)),
         }
     }
@@ -467,39 +665,48 @@ pub enum ConvertError {
 impl std::fmt::Display for ConvertError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ConvertError::InvalidChar(c) => write!(f, "found invalid char in ResultStateMask: '{}'\n  avialable chars are: 'r', 'b' and 'y'", c),
+            ConvertError::InvalidChar(c) => write!(f, "found invalid char in ResultStateMask: '{}'\n  avialable chars are: 'r', 'b' and 'y', or the squares 🟥/🟩, 🟨/🟡 and 🟦/⬛/⬜", c),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::alphabet::Alphabet;
+
+    fn code(word: &str) -> Vec<u8> {
+        Alphabet::default().encode(word).unwrap()
+    }
+
     #[test]
     fn default_result_state() {
         use super::ResultState;
 
-        let rs1 = ResultState::new(6);
+        let rs1 = ResultState::new(6, true);
         assert_eq!(rs1.to_string(), "rbbbbb");
 
-        let rs2 = ResultState::new(9);
+        let rs2 = ResultState::new(9, true);
         assert_eq!(rs2.to_string(), "rbbbbbbbb");
+
+        let rs3 = ResultState::new(5, false);
+        assert_eq!(rs3.to_string(), "bbbbb");
     }
 
     #[test]
     fn compare_result_state() {
         use super::ResultState;
 
-        let mut rs1 = ResultState::new(6);
-        rs1.update_with("mourir", "manger").unwrap();
+        let mut rs1 = ResultState::new(6, true);
+        rs1.update_with(&code("mourir"), &code("manger")).unwrap();
         assert_eq!(rs1.to_string(), "rbbbbr");
 
-        let mut rs2 = ResultState::new(7);
-        rs2.update_with("marines", "manager").unwrap();
+        let mut rs2 = ResultState::new(7, true);
+        rs2.update_with(&code("marines"), &code("manager")).unwrap();
         assert_eq!(rs2.to_string(), "rrybyrb");
 
         // guess isn't actualy a real word, but it will be fine
-        let mut rs3 = ResultState::new(8);
-        rs3.update_with("mozozzgz", "montagne").unwrap();
+        let mut rs3 = ResultState::new(8, true);
+        rs3.update_with(&code("mozozzgz"), &code("montagne")).unwrap();
         assert_eq!(rs3.to_string(), "rrbbbbyb");
     }
 
@@ -519,20 +726,114 @@ mod tests {
         });
     }
 
+    #[test]
+    fn unrevealed_mask() {
+        use super::{Mask, ResultState};
+
+        let fr = Alphabet::default();
+        let dico: Vec<Vec<u8>> = ["carte", "crane", "plage", "tarte"].iter().map(|w| code(w)).collect();
+        let mut mask = Mask::new(&fr, None, 5);
+        assert_eq!(mask.first(), None);
+        assert_eq!(mask.candidates(&dico).unwrap(), [0, 1, 2, 3]);
+
+        let mut rs = ResultState::new(5, false);
+        rs.update_with(&code("tarte"), &code("carte")).unwrap();
+        assert_eq!(rs.to_string(), "brrrr");
+
+        mask.update(&code("tarte"), &rs).unwrap();
+        assert_eq!(mask.candidates(&dico).unwrap(), [0]);
+    }
+
+    #[test]
+    fn emoji_result_state() {
+        use super::{Palette, ResultState};
+
+        let rs: ResultState = "🟥🟦🟡🟦🟦🟥".try_into().unwrap();
+        assert_eq!(rs.to_string(), "rbybbr");
+        assert_eq!(rs.squares(Palette::Tusmo), "🟥🟦🟡🟦🟦🟥");
+
+        // copied from a share text, with variation selectors
+        let rs: ResultState = "⬛\u{fe0f}🟨🟩 ⬜\u{fe0f}🟩".try_into().unwrap();
+        assert_eq!(rs.to_string(), "byrbr");
+        assert_eq!(rs.squares(Palette::Wordle), "⬛🟨🟩⬛🟩");
+
+        assert!(ResultState::try_from("🟥🟧").is_err());
+    }
+
+    #[test]
+    fn parse_mask() {
+        use super::Mask;
+
+        let fr = Alphabet::default();
+        let dico: Vec<Vec<u8>> = ["bailler", "balleur", "bouller", "brillez", "bullera"]
+            .iter().map(|w| code(w)).collect();
+
+        let mask = Mask::parse("b..ll..", &fr).unwrap();
+        assert_eq!(mask.first(), Some(1));
+        assert_eq!(mask.word_len(), 7);
+        assert_eq!(mask.candidates(&dico).unwrap(), [0, 2, 3]);
+
+        let mask = Mask::parse("b..ll.. +e -z 2^a", &fr).unwrap();
+        assert_eq!(mask.candidates(&dico).unwrap(), [2]);
+
+        let mask = Mask::parse("b.. +ll -e", &fr).unwrap();
+        assert!(mask.candidates(&dico).is_err());
+
+        assert!(Mask::parse("b..ll.. +E", &fr).is_err());
+        assert!(Mask::parse("b..ll.. 8^a", &fr).is_err());
+        assert!(Mask::parse("b..ll.. =a", &fr).is_err());
+    }
+
+    #[test]
+    fn conflicting_results() {
+        use super::ResultState;
+
+        let rs1: ResultState = "rrybyrb".try_into().unwrap();
+        let rs2: ResultState = "rbbbbbb".try_into().unwrap();
+        assert_eq!(rs2.conflict(&code("manager"), &rs1, &code("marines")), Some(0));
+
+        let rs1: ResultState = "rbbbbr".try_into().unwrap();
+        let rs2: ResultState = "rybbbb".try_into().unwrap();
+        assert_eq!(rs2.conflict(&code("mouche"), &rs1, &code("mourir")), Some(14));
+
+        let rs2: ResultState = "rbbbbb".try_into().unwrap();
+        assert_eq!(rs2.conflict(&code("mouche"), &rs1, &code("mourir")), None);
+
+        let rs: ResultState = "rbbybb".try_into().unwrap();
+        assert_eq!(rs.check(&code("mammal")), Some(12));
+
+        let rs: ResultState = "rbybbb".try_into().unwrap();
+        assert_eq!(rs.check(&code("mammal")), None);
+    }
+
     #[test]
     fn red_char() {
         use super::LetterMask;
 
         let a = LetterMask(1 << 0);
-        assert_eq!(a.red_char(), Some(b'a'));
+        assert_eq!(a.red_char(), Some(0));
 
         let m = LetterMask(1 << 12);
-        assert_eq!(m.red_char(), Some(b'm'));
+        assert_eq!(m.red_char(), Some(12));
 
         let z = LetterMask(1 << 25);
-        assert_eq!(z.red_char(), Some(b'z'));
+        assert_eq!(z.red_char(), Some(25));
 
         let oops = LetterMask((1 << 6) + (1 << 18));
         assert_eq!(oops.red_char(), None);
     }
+
+    #[test]
+    fn debug_letters() {
+        use super::Mask;
+
+        let mask = Mask::parse("c.... -z", &Alphabet::default()).unwrap();
+        let debug = format!("{:?}", mask);
+        let rows: Vec<&str> = debug.lines().collect();
+
+        assert_eq!(rows[0], format!("0:  c{}", " ".repeat(23)));
+        assert_eq!(rows[1], "1:abcdefghijklmnopqrstuvwxy ");
+        assert!(rows[5].starts_with("a:0+ b:0+ c:1+ d:0+ "));
+        assert!(rows[5].ends_with("y:0+ z:0! "));
+    }
 }
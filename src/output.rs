@@ -0,0 +1,162 @@
+use serde::Serialize;
+
+use crate::analyze::Report;
+use crate::mask::ResultState;
+
+/// how the results are printed
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// sentences for humans
+    Text,
+    /// one json object per event and per row, see the README for the schema
+    Json,
+}
+
+impl Format {
+    pub fn print(&self, event: &Event) {
+        match self {
+            Format::Text => print!("{}", event),
+            Format::Json => match serde_json::to_string(event) {
+                Ok(json) => println!("{}", json),
+                Err(err) => eprintln!("{}", err),
+            },
+        }
+    }
+
+    pub fn is_json(&self) -> bool {
+        *self == Format::Json
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err("format must be text or json"),
+        }
+    }
+}
+
+/// word with its average count of words left, null when it's a single word whatever the answer is
+#[derive(Serialize)]
+pub struct Scored<'a> {
+    pub word: &'a str,
+    pub score: f32,
+}
+
+/// what the commands print, the name of the variant is given in the "event" field in json
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    /// dictionary of the game loaded
+    Loaded { words: usize, lang: &'a str, micros: u128 },
+    /// turn read from a saved game
+    Replayed { word: &'a str, result: &'a ResultState },
+    /// words still matching after a turn, with more than one word
+    Remaining { count: usize, candidates: Vec<&'a str> },
+    /// the only word still matching
+    Obviously { word: &'a str },
+    /// best word to play, followed by the best words and their score
+    Suggestion { word: &'a str, score: f32, seconds: f32, ranking: Vec<Scored<'a>> },
+    /// word played with its result
+    Played { word: &'a str, result: &'a ResultState },
+    /// grade of a word played in hint mode
+    Grade { word: &'a str, score: f32, best: &'a str, best_score: f32 },
+    /// word played in hint mode that isn't in the dictionary
+    NotGraded { word: &'a str },
+    /// share of the best score reached on average, in percent
+    Skill { skill: f32, graded: usize, optimal: usize },
+    /// colored squares of the game
+    Share { text: String },
+    /// best first word of a len
+    Bench { len: u8, word: &'a str, index: usize, seconds: f32 },
+    /// words matching a query
+    Matching { count: usize, words: Vec<&'a str> },
+    /// report of the analyze command
+    Analysis(&'a Report),
+}
+
+/// the text printed without the json format, ending with a newline
+impl std::fmt::Display for Event<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Event::Loaded { words, lang, micros } => writeln!(f, "Dico loaded in {}µs ({} words, {})", micros, words, lang),
+            Event::Replayed { word, result } => writeln!(f, "Replayed: {} {}", word, result),
+            Event::Remaining { count, .. } => writeln!(f, "{} words remaining", count),
+            Event::Obviously { word } => writeln!(f, "Obviously: {}", word),
+            Event::Suggestion { word, score, seconds, .. } => {
+                writeln!(f, "Word found in {:.2}s", seconds)?;
+                writeln!(f, "Best word: {} ({:.2})", word, score)
+            }
+            Event::Played { result, .. } => writeln!(f, "Result: {}", result),
+            Event::Grade { score, best_score, .. } if score <= best_score => {
+                writeln!(f, "Your guess leaves {:.2} words on average, as good as the best word", score)
+            }
+            Event::Grade { score, best, best_score, .. } => {
+                writeln!(f, "Your guess leaves {:.2} words on average, best was {} ({:.2})", score, best, best_score)
+            }
+            Event::NotGraded { word } => writeln!(f, "'{}' is not in the dictionary, it can't be graded", word),
+            Event::Skill { skill, graded, optimal } => {
+                writeln!(f, "Skill: {:.0}% over {} graded guesses, {} as good as the best word", skill, graded, optimal)
+            }
+            Event::Share { text } => write!(f, "\n{}\n", text),
+            Event::Bench { len, word, index, seconds } => {
+                writeln!(f, "For words of len {}, best word is {} ({}) in {:.2}s", len, word, index, seconds)
+            }
+            Event::Matching { count, words } => {
+                for word in words {
+                    writeln!(f, "{}", word)?;
+                }
+
+                writeln!(f, "{} words matching", count)
+            }
+            Event::Analysis(report) => write!(f, "{}", report),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn json_events() {
+        use super::{Event, Scored};
+        use crate::analyze::Report;
+        use crate::mask::{ConvertError, ResultState};
+
+        let result: ResultState = "rybbb".try_into().unwrap_or_else(|err: ConvertError| panic!("{}", err));
+        let report = Report { answer: "carte".to_string(), game: "wordle 5".to_string(), turns: Vec::new(), found: true, summary: None };
+
+        // the README documents these objects for scripts
+        let events = [
+            (Event::Loaded { words: 2, lang: "fr", micros: 10 }, r#"{"event":"loaded","words":2,"lang":"fr","micros":10}"#),
+            (Event::Replayed { word: "carte", result: &result }, r#"{"event":"replayed","word":"carte","result":"rybbb"}"#),
+            (Event::Remaining { count: 2, candidates: vec!["carte", "crane"] }, r#"{"event":"remaining","count":2,"candidates":["carte","crane"]}"#),
+            (Event::Obviously { word: "carte" }, r#"{"event":"obviously","word":"carte"}"#),
+            (
+                Event::Suggestion { word: "carte", score: 1.5, seconds: 0.5, ranking: vec![Scored { word: "carte", score: 1.5 }, Scored { word: "crane", score: f32::NAN }] },
+                r#"{"event":"suggestion","word":"carte","score":1.5,"seconds":0.5,"ranking":[{"word":"carte","score":1.5},{"word":"crane","score":null}]}"#,
+            ),
+            (Event::Played { word: "carte", result: &result }, r#"{"event":"played","word":"carte","result":"rybbb"}"#),
+            (
+                Event::Grade { word: "crane", score: 2.5, best: "carte", best_score: 1.5 },
+                r#"{"event":"grade","word":"crane","score":2.5,"best":"carte","best_score":1.5}"#,
+            ),
+            (Event::NotGraded { word: "carte" }, r#"{"event":"not_graded","word":"carte"}"#),
+            (Event::Skill { skill: 50.0, graded: 2, optimal: 1 }, r#"{"event":"skill","skill":50.0,"graded":2,"optimal":1}"#),
+            (Event::Share { text: "🟥🟥".to_string() }, r#"{"event":"share","text":"🟥🟥"}"#),
+            (Event::Bench { len: 5, word: "carte", index: 0, seconds: 0.5 }, r#"{"event":"bench","len":5,"word":"carte","index":0,"seconds":0.5}"#),
+            (Event::Matching { count: 1, words: vec!["carte"] }, r#"{"event":"matching","count":1,"words":["carte"]}"#),
+            (
+                Event::Analysis(&report),
+                r#"{"event":"analysis","answer":"carte","game":"wordle 5","turns":[],"found":true,"summary":null}"#,
+            ),
+        ];
+
+        for (event, json) in &events {
+            assert_eq!(serde_json::to_string(event).unwrap(), *json);
+        }
+    }
+}
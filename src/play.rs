@@ -0,0 +1,201 @@
+use std::io::Write;
+use std::path::Path;
+
+use crate::alphabet::Alphabet;
+use crate::board;
+use crate::dico::Dico;
+use crate::hint::Hint;
+use crate::mask::ResultState;
+use crate::random::Rng;
+use crate::rules::Rules;
+use crate::session::Session;
+use crate::MaskDescriptor;
+
+pub struct Options {
+    pub tries: usize,
+    pub seed: u64,
+    /// grade each guess against the best word
+    pub hint: bool,
+    /// first row of the share text instead of the rules and the descriptor, like the date of a daily word
+    pub title: Option<String>,
+}
+
+/// the computer picks a word of the dictionary and grades the guesses of the player
+pub fn run(dir: &Path, rules: Rules, desc: Option<MaskDescriptor>, options: &Options) {
+    let (mut session, dico, answer) = match pick(dir, rules, desc, options.seed) {
+        Some(game) => game,
+        None => return,
+    };
+
+    let color = board::enabled();
+    let mut hint = Hint::default();
+
+    println!("Find the word of {} letters in {} tries (seed {})", session.desc().word_len(), options.tries, options.seed);
+    println!("{}", known(&session));
+
+    for turn in 1..=options.tries {
+        let guess = match read_guess(&session, &dico, &format!("Guess {}/{}: ", turn, options.tries)) {
+            Some(guess) => guess,
+            None => break,
+        };
+
+        // scores of the turn are needed before the guess is played
+        let grade = if options.hint {
+            session.scores(&dico.codes).ok().and_then(|scores| hint.grade(&scores, guess))
+        } else {
+            None
+        };
+
+        let mut result = ResultState::new(dico.codes[guess].len(), false);
+        let played = rules.feedback(&dico.codes[guess], &dico.codes[answer], &mut result)
+            .and_then(|()| session.play(&dico.words[guess], result.clone()));
+
+        if let Err(err) = played {
+            eprintln!("{}", err);
+            return;
+        }
+
+        print!("{}", board::render(&session, color));
+
+        if let Some(grade) = grade {
+            print!("{}", grade.event(&dico.words[guess], &dico.words[grade.best]));
+        }
+
+        if session.complet() {
+            println!("Found in {} tries!", turn);
+            break;
+        }
+    }
+
+    if !session.complet() {
+        println!("The word was {}", dico.words[answer]);
+    }
+
+    if let Some(summary) = hint.summary() {
+        print!("{}", summary);
+    }
+
+    if session.complet() {
+        println!();
+        println!("{}", session.share(options.title.as_deref()));
+    }
+}
+
+/// new game with a word picked from the seed among the words matching the descriptor,
+/// errors are printed
+pub fn pick(dir: &Path, rules: Rules, desc: Option<MaskDescriptor>, seed: u64) -> Option<(Session, Dico, usize)> {
+    let alphabet = match Alphabet::load(dir) {
+        Ok(alphabet) => alphabet,
+        Err(err) => {
+            eprintln!("{}", err);
+            return None;
+        }
+    };
+
+    let mut rng = Rng::new(seed);
+
+    let desc = match desc {
+        Some(desc) => desc,
+        None => match random_desc(dir, rules, &mut rng) {
+            Some(desc) => desc,
+            None => {
+                eprintln!("No dictionary found in {}", dir.display());
+                return None;
+            }
+        },
+    };
+
+    let session = match Session::new(desc, rules, alphabet) {
+        Ok(session) => session,
+        Err(err) => {
+            eprintln!("{}", err);
+            return None;
+        }
+    };
+
+    let dico = match session.desc().load(dir) {
+        Ok(dico) => dico,
+        Err(err) => {
+            eprintln!("{}", err);
+            return None;
+        }
+    };
+
+    // the answer must match the revealed letters
+    let answers = match session.candidates(&dico.codes) {
+        Ok(answers) if !answers.is_empty() => answers,
+        Ok(_) => {
+            eprintln!("No world of len {} found in {}", session.desc().word_len(), session.desc().path(dir).display());
+            return None;
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            return None;
+        }
+    };
+
+    Some((session, dico, answers[rng.below(answers.len())]))
+}
+
+/// letters revealed at the start, '.' for the unknown ones
+pub fn known(session: &Session) -> String {
+    session.known()
+        .into_iter()
+        .map(|letter| letter.unwrap_or(".").to_uppercase())
+        .collect()
+}
+
+/// random first letter and len for motus, 5 letters otherwise
+fn random_desc(dir: &Path, rules: Rules, rng: &mut Rng) -> Option<MaskDescriptor> {
+    if !rules.reveal_first() {
+        return Some(MaskDescriptor { dico: None, len: 5, pattern: None });
+    }
+
+    let letters = crate::dico::letters(dir).ok()?;
+    let letter = letters.get(rng.below(letters.len().max(1)))?.clone();
+
+    Some(MaskDescriptor { dico: Some(letter), len: 5 + rng.below(4) as u8, pattern: None })
+}
+
+/// id of the next valid guess of the player, None if the player leaves
+pub fn read_guess(session: &Session, dico: &Dico, prompt: &str) -> Option<usize> {
+    let mut buf = String::with_capacity(16);
+    let first = session.known()[0].filter(|_| session.rules().reveal_first());
+
+    loop {
+        buf.clear();
+        print!("{}", prompt);
+        std::io::stdout().flush().unwrap();
+
+        match std::io::stdin().read_line(&mut buf) {
+            Ok(0) => return None,
+            Ok(_) => (),
+            Err(err) => {
+                eprintln!("{}", err);
+                return None;
+            }
+        }
+
+        let guess = buf.trim().to_lowercase();
+
+        let code = match session.alphabet().encode(&guess) {
+            Ok(code) => code,
+            Err(c) => {
+                eprintln!("Invalid word '{}': invalid char '{}'", guess, c);
+                continue;
+            }
+        };
+
+        if guess.is_empty() {
+            continue;
+        } else if code.len() != session.desc().word_len() as usize {
+            eprintln!("The word must have {} letters", session.desc().word_len());
+        } else if first.is_some_and(|first| !guess.starts_with(first)) {
+            eprintln!("The word must start with '{}'", first.unwrap_or_default());
+        } else if let Some(id) = dico.position(&guess) {
+            return Some(id);
+        } else {
+            eprintln!("'{}' is not in the dictionary", guess);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::mask::{MaskError, Palette, ResultState};
+
+/// how a guess is graded against the answer
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Feedback {
+    /// one color per letter: red at its place, yellow elsewhere, blue if absent
+    Colors,
+    /// only the count of reds and yellows, without their place
+    Counts,
+}
+
+/// rules of the game being solved, the same `Mask` engine is used for all of them
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    pub feedback: Feedback,
+    /// true if the first letter is given before the first turn
+    pub reveal_first: bool,
+}
+
+impl Rules {
+    /// first letter revealed, one dictionary per first letter, also used by tusmo
+    pub const MOTUS: Rules = Rules { feedback: Feedback::Colors, reveal_first: true };
+    /// no revealed letter, 5 letters by default, words of every dictionary
+    pub const WORDLE: Rules = Rules { feedback: Feedback::Colors, reveal_first: false };
+    /// no revealed letter, only counts of reds and yellows
+    pub const MASTERMIND: Rules = Rules { feedback: Feedback::Counts, reveal_first: false };
+
+    pub fn reveal_first(&self) -> bool {
+        self.reveal_first
+    }
+
+    /// squares of the share text, like the site of the rules
+    pub fn palette(&self) -> Palette {
+        if self.reveal_first {
+            Palette::Tusmo
+        } else {
+            Palette::Wordle
+        }
+    }
+
+    /// true if the mask alone knows which words are left, otherwise the turns are replayed
+    pub fn positional(&self) -> bool {
+        self.feedback == Feedback::Colors
+    }
+
+    /// grade guess against target, counts are given as reds, then yellows, then blues
+    pub fn feedback(&self, guess: &[u8], target: &[u8], result: &mut ResultState) -> Result<(), MaskError> {
+        result.update_with(guess, target)?;
+
+        if self.feedback == Feedback::Counts {
+            result.sort();
+        }
+
+        Ok(())
+    }
+
+    /// true if target would have given each of these results
+    pub fn consistent<'a>(&self, target: &[u8], mut turns: impl Iterator<Item = (&'a [u8], &'a ResultState)>) -> bool {
+        let mut buffer = ResultState::new(target.len(), false);
+
+        turns.all(|(played, result)| {
+            self.feedback(played, target, &mut buffer).is_ok() && buffer == *result
+        })
+    }
+
+    /// average count of candidates left after playing each word of the dico, lower is better,
+    /// computed by grading every word against every candidate.
+    /// A candidate left alone only counts if it's the word played, so the words leaving
+    /// every candidate alone without being one of them are NaN
+    pub fn scores(&self, dico: &[Vec<u8>], candidates: &[usize]) -> Result<Vec<f32>, MaskError> {
+        let word_len = match candidates.first() {
+            Some(&id) => dico[id].len(),
+            None => return Err(MaskError::NoCandidate),
+        };
+
+        // count of candidates by result, one buffer per thread
+        let init = || (ResultState::new(word_len, false), vec![0_u32; 3_usize.pow(word_len as u32)]);
+
+        dico.par_iter().map_init(init, |(result, states), word| {
+            states.fill(0);
+
+            for &target in candidates {
+                self.feedback(word, &dico[target], result)?;
+                states[result.state_id()] += 1;
+            }
+
+            // the first state is the one of the word itself, every letter at its place
+            let found = (states[0] == 1) as u32;
+            let (sum, count) = states.iter()
+                .filter(|&&count| count > 1)
+                .fold((found, found), |(sum, total), &count| (sum + count * count, total + count));
+
+            Ok(sum as f32 / count as f32)
+        }).collect()
+    }
+}
+
+impl std::default::Default for Rules {
+    fn default() -> Self {
+        Rules::MOTUS
+    }
+}
+
+/// preset name, followed by "+first" or "-first" when the first letter is handled differently
+impl std::fmt::Display for Rules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let preset = match self.feedback {
+            Feedback::Colors if self.reveal_first => return write!(f, "motus"),
+            Feedback::Colors => "wordle",
+            Feedback::Counts => "mastermind",
+        };
+
+        if self.reveal_first {
+            write!(f, "{}+first", preset)
+        } else {
+            write!(f, "{}", preset)
+        }
+    }
+}
+
+impl std::str::FromStr for Rules {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (preset, reveal_first) = if let Some(preset) = s.strip_suffix("+first") {
+            (preset, Some(true))
+        } else if let Some(preset) = s.strip_suffix("-first") {
+            (preset, Some(false))
+        } else {
+            (s, None)
+        };
+
+        let mut rules = match preset {
+            "motus" | "tusmo" => Rules::MOTUS,
+            "wordle" => Rules::WORDLE,
+            "mastermind" => Rules::MASTERMIND,
+            _ => return Err("rules must be one of motus, wordle or mastermind, optionally followed by +first or -first"),
+        };
+
+        if let Some(reveal_first) = reveal_first {
+            rules.reveal_first = reveal_first;
+        }
+
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_rules() {
+        use super::Rules;
+
+        let rules: Rules = "tusmo".parse().unwrap();
+        assert!(rules == Rules::MOTUS);
+        assert_eq!(rules.to_string(), "motus");
+
+        let rules: Rules = "motus-first".parse().unwrap();
+        assert!(rules == Rules::WORDLE);
+
+        let rules: Rules = "mastermind+first".parse().unwrap();
+        assert!(rules.reveal_first() && !rules.positional());
+        assert_eq!(rules.to_string(), "mastermind+first");
+
+        assert!("scrabble".parse::<Rules>().is_err());
+    }
+
+    #[test]
+    fn counts_feedback() {
+        use super::Rules;
+        use crate::alphabet::Alphabet;
+        use crate::mask::ResultState;
+
+        let fr = Alphabet::default();
+        let code = |word| fr.encode(word).unwrap();
+
+        let mut result = ResultState::new(5, false);
+        Rules::MASTERMIND.feedback(&code("tarte"), &code("carte"), &mut result).unwrap();
+        assert_eq!(result.to_string(), "rrrrb");
+
+        Rules::MASTERMIND.feedback(&code("crane"), &code("carte"), &mut result).unwrap();
+        assert_eq!(result.to_string(), "rryyb");
+
+        let dico = [code("carte"), code("crane"), code("tarte")];
+        let turns = [(code("crane"), result.clone())];
+        let consistent: Vec<bool> = dico.iter()
+            .map(|word| Rules::MASTERMIND.consistent(word, turns.iter().map(|(w, r)| (w.as_slice(), r))))
+            .collect();
+
+        assert_eq!(consistent, [true, false, false]);
+    }
+
+    #[test]
+    fn scores() {
+        use super::Rules;
+        use crate::alphabet::Alphabet;
+
+        let fr = Alphabet::default();
+        let words = ["carte", "crane", "tarte", "verte", "porte", "pomme"];
+        let dico: Vec<Vec<u8>> = words.iter().map(|word| fr.encode(word).unwrap()).collect();
+
+        // pomme leaves 4 words with bbbbr and finds itself, porte alone isn't counted
+        let scores = Rules::WORDLE.scores(&dico, &(0..dico.len()).collect::<Vec<_>>()).unwrap();
+        assert_eq!(scores[5], 17.0 / 5.0);
+
+        // crane leaves carte and porte alone without being one of them
+        let scores = Rules::WORDLE.scores(&dico, &[0, 4]).unwrap();
+        assert_eq!(scores[0], 1.0);
+        assert!(scores[1].is_nan());
+    }
+}
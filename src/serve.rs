@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::alphabet::Alphabet;
+use crate::dico::Dico;
+use crate::mask::{self, Mask, ResultState};
+use crate::output::Scored;
+use crate::rules::Rules;
+use crate::session::Session;
+use crate::MaskDescriptor;
+
+/// count of suggestions by default
+const SUGGESTIONS: usize = 10;
+
+/// a request of the protocol, the name of the variant is given in the "cmd" field
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+enum Command {
+    /// start a game, its id is picked if not provided
+    New {
+        game: Option<String>,
+        /// revealed first letter, none for wordle
+        letter: Option<String>,
+        /// count of letters of the word, revealed one included
+        length: Option<u8>,
+        /// known letters like "b..l....", instead of the letter and the length
+        pattern: Option<String>,
+        rules: Option<String>,
+    },
+    /// result of a word played
+    Play { game: String, word: String, result: String },
+    /// best words to play with their score
+    Suggest { game: String, count: Option<usize> },
+    /// words still matching
+    Candidates { game: String },
+    /// cancel the last turn
+    Undo { game: String },
+    /// forget the game, with its share text
+    End { game: String },
+    /// words matching a pattern, like the query command
+    Query { spec: String },
+}
+
+/// every request is answered with the same "id" if it had one
+#[derive(Deserialize)]
+struct Request {
+    id: Option<Value>,
+    #[serde(flatten)]
+    command: Command,
+}
+
+struct Game {
+    session: Session,
+    dico: Rc<Dico>,
+}
+
+/// games in progress, answering the requests of the protocol
+pub struct Server {
+    dir: PathBuf,
+    alphabet: Alphabet,
+    games: HashMap<String, Game>,
+    /// dictionaries by first letter and len, loaded once for all the games
+    dicos: HashMap<(Option<String>, u8), Rc<Dico>>,
+    /// id of the next game without id
+    next: usize,
+}
+
+impl Server {
+    pub fn new(dir: &Path) -> Result<Self, crate::alphabet::AlphabetError> {
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            alphabet: Alphabet::load(dir)?,
+            games: HashMap::new(),
+            dicos: HashMap::new(),
+            next: 1,
+        })
+    }
+
+    /// answer a json request with a json object, "ok" is false with an "error" if it failed
+    pub fn handle(&mut self, line: &str) -> Value {
+        match serde_json::from_str::<Request>(line) {
+            Ok(request) => self.answer(request),
+            Err(err) => json!({ "ok": false, "error": format!("invalid request: {}", err) }),
+        }
+    }
+
+    fn answer(&mut self, request: Request) -> Value {
+        let mut reply = match self.run(request.command) {
+            Ok(mut reply) => {
+                reply["ok"] = json!(true);
+                reply
+            }
+            Err(error) => json!({ "ok": false, "error": error }),
+        };
+
+        if let Some(id) = request.id {
+            reply["id"] = id;
+        }
+
+        reply
+    }
+
+    fn dico(&mut self, desc: &MaskDescriptor) -> Result<Rc<Dico>, String> {
+        let key = (desc.dico.clone(), desc.word_len());
+
+        if let Some(dico) = self.dicos.get(&key) {
+            return Ok(dico.clone());
+        }
+
+        let dico = Rc::new(desc.load(&self.dir).map_err(|err| err.to_string())?);
+        self.dicos.insert(key, dico.clone());
+        Ok(dico)
+    }
+
+    fn run(&mut self, command: Command) -> Result<Value, String> {
+        match command {
+            Command::New { game, letter, length, pattern, rules } => {
+                let rules: Rules = match rules {
+                    Some(rules) => rules.parse()?,
+                    None => Rules::default(),
+                };
+
+                // the length counts the revealed letter, unlike the descriptors
+                let desc: MaskDescriptor = match (pattern, letter, length) {
+                    (Some(pattern), _, _) => pattern.parse()?,
+                    (None, Some(_), Some(length)) if !(6..=9).contains(&length) => {
+                        return Err("length must be from 6 to 9 with a letter".to_string());
+                    }
+                    (None, Some(letter), Some(length)) => format!("{}{}", letter, length - 1).parse()?,
+                    (None, Some(_), None) => return Err("missing length".to_string()),
+                    (None, None, _) if rules.reveal_first() => {
+                        return Err(format!("missing letter: the first letter is revealed with the {} rules", rules));
+                    }
+                    (None, None, Some(length)) if !(5..=8).contains(&length) => {
+                        return Err("length must be from 5 to 8 without letter".to_string());
+                    }
+                    (None, None, length) => length.unwrap_or(5).to_string().parse()?,
+                };
+
+                let session = Session::new(desc, rules, self.alphabet.clone()).map_err(|err| err.to_string())?;
+                let dico = self.dico(session.desc())?;
+                let remaining = session.candidates(&dico.codes).map_err(|err| err.to_string())?.len();
+
+                // ids given by the client are skipped
+                let game = match game {
+                    Some(game) => game,
+                    None => loop {
+                        let game = self.next.to_string();
+                        self.next += 1;
+
+                        if !self.games.contains_key(&game) {
+                            break game;
+                        }
+                    },
+                };
+
+                if self.games.contains_key(&game) {
+                    return Err(format!("game '{}' already exists", game));
+                }
+
+                let reply = json!({
+                    "game": game,
+                    "rules": rules.to_string(),
+                    "length": session.desc().word_len(),
+                    "known": session.known(),
+                    "remaining": remaining,
+                });
+
+                self.games.insert(game, Game { session, dico });
+                Ok(reply)
+            }
+            Command::Play { game, word, result } => {
+                let Game { session, dico } = self.game(&game)?;
+                let result = ResultState::try_from(result.as_str()).map_err(|err| err.to_string())?;
+
+                session.play(&word, result).map_err(|err| err.to_string())?;
+
+                // 0 when the answer isn't in the dictionary, the turn can be undone
+                let remaining = session.candidates(&dico.codes).map_err(|err| err.to_string())?.len();
+                Ok(json!({ "game": game, "remaining": remaining, "complete": session.complet() }))
+            }
+            Command::Suggest { game, count } => {
+                let Game { session, dico } = self.game(&game)?;
+                let candidates = session.candidates(&dico.codes).map_err(|err| err.to_string())?;
+
+                // the last word can't be scored, like the words leaving a single candidate whatever the answer
+                let suggestions: Vec<Scored> = match candidates.as_slice() {
+                    [] => return Err(mask::MaskError::NoCandidate.to_string()),
+                    &[id] => vec![Scored { word: &dico.words[id], score: f32::NAN }],
+                    _ => {
+                        let scores = session.scores(&dico.codes).map_err(|err| err.to_string())?;
+
+                        mask::rank(&scores)
+                            .into_iter()
+                            .filter(|&id| !scores[id].is_nan())
+                            .take(count.unwrap_or(SUGGESTIONS))
+                            .map(|id| Scored { word: &dico.words[id], score: scores[id] })
+                            .collect()
+                    }
+                };
+
+                Ok(json!({ "game": game, "suggestions": suggestions }))
+            }
+            Command::Candidates { game } => {
+                let Game { session, dico } = self.game(&game)?;
+                let candidates: Vec<&str> = session.candidates(&dico.codes)
+                    .map_err(|err| err.to_string())?
+                    .into_iter()
+                    .map(|id| dico.words[id].as_str())
+                    .collect();
+
+                Ok(json!({ "game": game, "count": candidates.len(), "candidates": candidates }))
+            }
+            Command::Undo { game } => {
+                let Game { session, .. } = self.game(&game)?;
+
+                match session.undo() {
+                    Some((word, result)) => Ok(json!({ "game": game, "word": word, "result": result })),
+                    None => Err("nothing to undo".to_string()),
+                }
+            }
+            Command::End { game } => match self.games.remove(&game) {
+                Some(Game { session, .. }) => Ok(json!({ "game": game, "share": session.share(None) })),
+                None => Err(format!("no game '{}'", game)),
+            },
+            Command::Query { spec } => {
+                let mask = Mask::parse(&spec, &self.alphabet).map_err(|err| err.to_string())?;
+
+                let dico = self.dico(&MaskDescriptor::of_mask(&mask, &self.alphabet))?;
+
+                let words: Vec<&str> = mask.candidates(&dico.codes)
+                    .map_err(|err| err.to_string())?
+                    .into_iter()
+                    .map(|id| dico.words[id].as_str())
+                    .collect();
+
+                Ok(json!({ "count": words.len(), "words": words }))
+            }
+        }
+    }
+
+    fn game(&mut self, game: &str) -> Result<&mut Game, String> {
+        self.games.get_mut(game).ok_or_else(|| format!("no game '{}'", game))
+    }
+}
+
+/// answer each request of stdin with a line on stdout, until stdin is closed
+pub fn stdio(dir: &Path) {
+    let mut server = match Server::new(dir) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    let mut stdout = std::io::stdout();
+
+    for line in std::io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => line,
+            Err(err) => {
+                eprintln!("{}", err);
+                return;
+            }
+        };
+
+        let reply = server.handle(&line);
+
+        if writeln!(stdout, "{}", reply).and_then(|()| stdout.flush()).is_err() {
+            return;
+        }
+    }
+}
+
+/// request of the protocol for a route of the http api, the body gives the other fields,
+/// None for an unknown route
+fn route(method: &tiny_http::Method, url: &str, mut body: Value) -> Result<Option<Value>, String> {
+    use tiny_http::Method;
+
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+
+    let (cmd, game) = match (method, segments.as_slice()) {
+        (Method::Post, ["games"]) => ("new", None),
+        (Method::Post, ["games", game, "guesses"]) => ("play", Some(game)),
+        (Method::Get, ["games", game, "suggestions"]) => ("suggest", Some(game)),
+        (Method::Get, ["games", game, "candidates"]) => ("candidates", Some(game)),
+        (Method::Post, ["games", game, "undo"]) => ("undo", Some(game)),
+        (Method::Delete, ["games", game]) => ("end", Some(game)),
+        (Method::Post, ["query"]) => ("query", None),
+        _ => return Ok(None),
+    };
+
+    let fields = body.as_object_mut().ok_or("invalid request: body must be an object")?;
+    fields.insert("cmd".to_string(), json!(cmd));
+
+    if let Some(game) = game {
+        fields.insert("game".to_string(), json!(game));
+    }
+
+    // only the count of suggestions is given in the url
+    if let Some(count) = query.split('&').find_map(|param| param.strip_prefix("count=")?.parse::<usize>().ok()) {
+        fields.insert("count".to_string(), json!(count));
+    }
+
+    Ok(Some(body))
+}
+
+/// answer the requests of the http api until the process is stopped
+pub fn http(dir: &Path, address: &str) {
+    let mut server = match Server::new(dir) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    let http = match tiny_http::Server::http(address) {
+        Ok(http) => http,
+        Err(err) => {
+            eprintln!("Unable to listen on {}: {}", address, err);
+            return;
+        }
+    };
+
+    println!("Listening on http://{}", address);
+
+    for mut request in http.incoming_requests() {
+        let mut content = String::new();
+
+        let body = match request.as_reader().read_to_string(&mut content) {
+            Ok(_) if content.trim().is_empty() => Ok(json!({})),
+            Ok(_) => serde_json::from_str::<Value>(&content).map_err(|err| format!("invalid request: {}", err)),
+            Err(err) => Err(err.to_string()),
+        };
+
+        let (status, reply) = match body.and_then(|body| route(request.method(), request.url(), body)) {
+            Ok(Some(command)) => match serde_json::from_value::<Request>(command) {
+                Ok(command) => {
+                    let reply = server.answer(command);
+                    (if reply["ok"] == true { 200 } else { 400 }, reply)
+                }
+                Err(err) => (400, json!({ "ok": false, "error": format!("invalid request: {}", err) })),
+            },
+            Ok(None) => (404, json!({ "ok": false, "error": "unknown route" })),
+            Err(error) => (400, json!({ "ok": false, "error": error })),
+        };
+
+        let header = tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = tiny_http::Response::from_string(reply.to_string())
+            .with_status_code(status)
+            .with_header(header);
+
+        if let Err(err) = request.respond(response) {
+            eprintln!("{}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn protocol() {
+        use super::Server;
+
+        let dir = std::env::temp_dir().join(format!("motus-serve-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("c.txt"), "carte\ncrane\n").unwrap();
+        std::fs::write(dir.join("t.txt"), "tarte\ntartes\n").unwrap();
+
+        let mut server = Server::new(&dir).unwrap_or_else(|err| panic!("{}", err));
+
+        let reply = server.handle(r#"{"id": 7, "cmd": "new", "rules": "wordle"}"#);
+        assert_eq!(reply["id"], 7);
+        assert_eq!((&reply["game"], &reply["remaining"]), (&"1".into(), &3.into()));
+
+        let reply = server.handle(r#"{"cmd": "new", "game": "b", "pattern": "c...."}"#);
+        assert_eq!(reply["remaining"], 2);
+
+        let reply = server.handle(r#"{"cmd": "new", "letter": "c"}"#);
+        assert_eq!(reply["error"], "missing length");
+
+        let reply = server.handle(r#"{"cmd": "new", "length": 5}"#);
+        assert_eq!(reply["error"], "missing letter: the first letter is revealed with the motus rules");
+
+        let reply = server.handle(r#"{"cmd": "new", "letter": "t", "length": 5}"#);
+        assert_eq!(reply["error"], "length must be from 6 to 9 with a letter");
+
+        let reply = server.handle(r#"{"cmd": "new", "rules": "wordle", "length": 9}"#);
+        assert_eq!(reply["error"], "length must be from 5 to 8 without letter");
+
+        let reply = server.handle(r#"{"cmd": "new", "game": "c", "letter": "t", "length": 6}"#);
+        assert_eq!(reply["remaining"], 1);
+
+        let reply = server.handle(r#"{"cmd": "suggest", "game": "c"}"#);
+        assert_eq!(reply["suggestions"], serde_json::json!([{ "word": "tartes", "score": null }]));
+
+        let reply = server.handle(r#"{"cmd": "play", "game": "1", "word": "tarte", "result": "brrrr"}"#);
+        assert_eq!((&reply["remaining"], &reply["complete"]), (&1.into(), &false.into()));
+
+        let reply = server.handle(r#"{"cmd": "suggest", "game": "1"}"#);
+        assert_eq!(reply["suggestions"][0]["word"], "carte");
+
+        let reply = server.handle(r#"{"cmd": "undo", "game": "1"}"#);
+        assert_eq!(reply["word"], "tarte");
+
+        let reply = server.handle(r#"{"cmd": "candidates", "game": "1"}"#);
+        assert_eq!(reply["count"], 3);
+
+        let reply = server.handle(r#"{"cmd": "end", "game": "1"}"#);
+        assert_eq!(reply["ok"], true);
+
+        let reply = server.handle(r#"{"cmd": "query", "spec": "c...e -n"}"#);
+        assert_eq!(reply["words"], serde_json::json!(["carte"]));
+
+        let reply = server.handle(r#"{"cmd": "undo", "game": "1"}"#);
+        assert_eq!((&reply["ok"], &reply["error"]), (&false.into(), &"no game '1'".into()));
+        assert_eq!(server.handle("{}")["ok"], false);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn http_routes() {
+        use super::route;
+        use serde_json::json;
+        use tiny_http::Method;
+
+        let command = route(&Method::Get, "/games/b/suggestions?count=3", json!({}));
+        assert_eq!(command, Ok(Some(json!({ "cmd": "suggest", "game": "b", "count": 3 }))));
+
+        let command = route(&Method::Post, "/games", json!({ "letter": "b" }));
+        assert_eq!(command, Ok(Some(json!({ "cmd": "new", "letter": "b" }))));
+
+        assert_eq!(route(&Method::Get, "/games", json!({})), Ok(None));
+        assert_eq!(route(&Method::Post, "/query", json!([])), Err("invalid request: body must be an object".to_string()));
+    }
+}
@@ -0,0 +1,418 @@
+use std::io::{BufReader, BufRead, Write};
+use std::path::Path;
+
+use crate::alphabet::Alphabet;
+use crate::mask::{Mask, MaskError, ResultState};
+use crate::rules::Rules;
+use crate::MaskDescriptor;
+
+/// a game in progress: the descriptor it started from and every played turn
+pub struct Session {
+    desc: MaskDescriptor,
+    rules: Rules,
+    alphabet: Alphabet,
+    mask: Mask,
+    history: Vec<(String, ResultState)>,
+    /// code of each played word, see `Alphabet::encode`
+    codes: Vec<Vec<u8>>,
+}
+
+impl Session {
+    pub fn new(mut desc: MaskDescriptor, rules: Rules, alphabet: Alphabet) -> Result<Self, SessionError> {
+        let mask = Self::start_mask(&desc, &alphabet)?;
+
+        if desc.pattern.is_some() {
+            if !(5..=9).contains(&mask.word_len()) {
+                return Err(SessionError::InvalidPattern("words must have from 5 to 9 letters".to_string()));
+            }
+
+            // a pattern may reveal the first letter whatever the rules are
+            desc.dico = mask.first().map(|first| alphabet.letter(first).to_string());
+            desc.len = (mask.word_len() - desc.dico.is_some() as usize) as u8;
+
+            if rules.reveal_first() && desc.dico.is_none() {
+                return Err(SessionError::IncompatibleRules(rules));
+            }
+        } else if desc.dico.is_some() != rules.reveal_first() {
+            return Err(SessionError::IncompatibleRules(rules));
+        }
+
+        Ok(Self { desc, rules, alphabet, mask, history: Vec::new(), codes: Vec::new() })
+    }
+
+    /// mask before the first turn, with the revealed letters if any
+    fn start_mask(desc: &MaskDescriptor, alphabet: &Alphabet) -> Result<Mask, SessionError> {
+        if let Some(pattern) = &desc.pattern {
+            return Mask::parse(pattern, alphabet).map_err(|err| SessionError::InvalidPattern(err.to_string()));
+        }
+
+        let start = match &desc.dico {
+            Some(letter) => Some(alphabet.index(letter).ok_or_else(|| SessionError::UnknownLetter(letter.clone()))?),
+            None => None,
+        };
+
+        Ok(Mask::new(alphabet, start, desc.len))
+    }
+
+    pub fn desc(&self) -> &MaskDescriptor {
+        &self.desc
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+
+    pub fn history(&self) -> &[(String, ResultState)] {
+        &self.history
+    }
+
+    /// letter at each place, if the results revealed it
+    pub fn known(&self) -> Vec<Option<&str>> {
+        self.mask.known().into_iter().map(|letter| letter.map(|i| self.alphabet.letter(i))).collect()
+    }
+
+    /// true once the last played word was the right one
+    pub fn complet(&self) -> bool {
+        self.history.last().is_some_and(|(_, result)| result.complet())
+    }
+
+    /// ids of the words of dico that would have given every result
+    pub fn candidates(&self, dico: &[Vec<u8>]) -> Result<Vec<usize>, MaskError> {
+        let mut candidates = self.mask.candidates(dico)?;
+
+        // without places, the mask only knows the revealed letter
+        if !self.rules.positional() {
+            let turns = || self.codes.iter().map(Vec::as_slice).zip(self.history.iter().map(|(_, result)| result));
+            candidates.retain(|&id| self.rules.consistent(&dico[id], turns()));
+        }
+
+        Ok(candidates)
+    }
+
+    /// score of each word of the dico with the rules of the game, see `Rules::scores`
+    pub fn scores(&self, dico: &[Vec<u8>]) -> Result<Vec<f32>, MaskError> {
+        self.rules.scores(dico, &self.candidates(dico)?)
+    }
+
+    /// what the solver would do next: the words left and the best word to play among the dico
+    pub fn next_step(&self, dico: &[Vec<u8>]) -> Result<Step, MaskError> {
+        let start = std::time::Instant::now();
+
+        let candidates = self.candidates(dico)?;
+
+        match candidates.as_slice() {
+            [] => return Ok(Step::NoWord),
+            &[id] => return Ok(Step::Obviously(id)),
+            _ => (),
+        }
+
+        let scores = self.rules.scores(dico, &candidates)?;
+        let (best, score) = crate::mask::best(&scores).ok_or(MaskError::NoCandidate)?;
+
+        Ok(Step::Suggestion { candidates, best, score, scores, seconds: start.elapsed().as_secs_f32() })
+    }
+
+    /// refuse the result if it contradicts itself or any previous turn
+    pub fn play(&mut self, word: &str, mut result: ResultState) -> Result<(), MaskError> {
+        let code = self.alphabet.encode(word).map_err(MaskError::InvalidChar)?;
+        let letter = |index| self.alphabet.letter(index).to_string();
+
+        if !self.rules.positional() {
+            if result.word_len() != code.len() || code.len() != self.mask.word_len() {
+                return Err(MaskError::IncompatibleLen("play"));
+            }
+
+            // the mask only knows the revealed letters, the word must have them at their place
+            let revealed = code.iter().zip(self.mask.known()).find(|&(&c, known)| known.is_some_and(|known| known != c));
+            if let Some((&index, _)) = revealed {
+                return Err(MaskError::Revealed(letter(index)));
+            }
+
+            // only counts are given, whatever the order of the colors
+            result.sort();
+            self.history.push((word.to_string(), result));
+            self.codes.push(code);
+
+            return Ok(());
+        }
+
+        let mut mask = self.mask.clone();
+        mask.update(&code, &result)?;
+
+        if let Some(index) = result.check(&code) {
+            return Err(MaskError::Inconsistent(letter(index)));
+        }
+
+        let played = self.history.iter().zip(&self.codes).enumerate();
+        for (turn, ((_, played_result), played)) in played {
+            if let Some(index) = result.conflict(&code, played_result, played) {
+                return Err(MaskError::Contradiction(turn + 1, letter(index)));
+            }
+        }
+
+        match self.contradiction(&code, &result) {
+            Some((0, index)) => return Err(MaskError::Revealed(letter(index))),
+            Some((turn, index)) => return Err(MaskError::Contradiction(turn, letter(index))),
+            None => (),
+        }
+
+        self.mask = mask;
+        self.history.push((word.to_string(), result));
+        self.codes.push(code);
+
+        Ok(())
+    }
+
+    /// first turn after which the mask refuses the result, 0 for the revealed letters
+    fn contradiction(&self, code: &[u8], result: &ResultState) -> Option<(usize, u8)> {
+        self.mask.conflict(code, result)?;
+
+        // the mask is rebuilt turn by turn to find the one that knew the letter
+        let mut mask = Self::start_mask(&self.desc, &self.alphabet).ok()?;
+
+        for (turn, ((_, played_result), played)) in self.history.iter().zip(&self.codes).enumerate() {
+            if let Some(index) = mask.conflict(code, result) {
+                return Some((turn, index));
+            }
+
+            mask.update(played, played_result).ok()?;
+        }
+
+        mask.conflict(code, result).map(|index| (self.history.len(), index))
+    }
+
+    /// cancel the last turn, the mask is rebuilt from the remaining ones
+    pub fn undo(&mut self) -> Option<(String, ResultState)> {
+        let last = self.history.pop()?;
+        self.codes.pop();
+
+        // the start letter was already checked by `new`
+        self.mask = Self::start_mask(&self.desc, &self.alphabet).ok()?;
+
+        for ((_, result), code) in self.history.iter().zip(&self.codes) {
+            // those turns were already accepted with this mask
+            self.mask.update(code, result).ok()?;
+        }
+
+        Some(last)
+    }
+
+    /// results as colored squares, like the share text of the sites
+    /// the title replaces the rules and the descriptor before the count of turns, like the date of a daily word
+    pub fn share(&self, title: Option<&str>) -> String {
+        let palette = self.rules.palette();
+        let mut share = match title {
+            Some(title) => format!("{} {}", title, self.history.len()),
+            None => format!("{} {} {}", self.rules, self.desc, self.history.len()),
+        };
+
+        for (_, result) in &self.history {
+            share.push('\n');
+            share.push_str(&result.squares(palette));
+        }
+
+        share
+    }
+
+    /// file starts with the descriptor, prefixed by the rules if they aren't motus,
+    /// followed by one "<word> <result>" row per turn
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut file = std::fs::File::create(path)?;
+
+        if self.rules == Rules::MOTUS {
+            writeln!(file, "{}", self.desc)?;
+        } else {
+            writeln!(file, "{} {}", self.rules, self.desc)?;
+        }
+
+        for (word, result) in &self.history {
+            writeln!(file, "{} {}", word, result)?;
+        }
+
+        Ok(())
+    }
+
+    /// replay every saved turn through `Mask::update`
+    pub fn load(path: &Path, alphabet: Alphabet) -> Result<Self, SessionError> {
+        let file = std::fs::File::open(path).map_err(|_| SessionError::NoFile(path.display().to_string()))?;
+        let mut rows = BufReader::new(file).lines().enumerate();
+
+        let row = match rows.next() {
+            Some((_, Ok(row))) => row,
+            Some((_, Err(_))) => return Err(SessionError::ReadingFile),
+            None => return Err(SessionError::InvalidRow(1, "missing descriptor".to_string())),
+        };
+
+        // the rules are only saved when they aren't motus, and patterns may have spaces
+        let (rules, desc) = match row.trim().split_once(' ').map(|(rules, desc)| (rules.parse::<Rules>(), desc)) {
+            Some((Ok(rules), desc)) => (rules, desc.trim()),
+            _ => (Rules::MOTUS, row.trim()),
+        };
+
+        let desc = desc.parse().map_err(|err: &str| SessionError::InvalidRow(1, err.to_string()))?;
+
+        let mut session = Self::new(desc, rules, alphabet)?;
+
+        for (line, row) in rows {
+            let row = row.map_err(|_| SessionError::ReadingFile)?;
+            let line = line + 1;
+
+            if row.trim().is_empty() {
+                continue;
+            }
+
+            let (word, result) = row.trim().split_once(' ')
+                .ok_or_else(|| SessionError::InvalidRow(line, "expected \"<word> <result>\"".to_string()))?;
+
+            let result: ResultState = result.trim().try_into()
+                .map_err(|err: crate::mask::ConvertError| SessionError::InvalidRow(line, err.to_string()))?;
+
+            session.play(word, result)
+                .map_err(|err| SessionError::InvalidRow(line, err.to_string()))?;
+        }
+
+        Ok(session)
+    }
+}
+
+/// next move of the solver, see `Session::next_step`
+pub enum Step {
+    /// no word of the dico matches the results
+    NoWord,
+    /// id of the only word left
+    Obviously(usize),
+    /// ids of the words left, then the best word with its score, the scores of every word
+    /// and the time they took
+    Suggestion { candidates: Vec<usize>, best: usize, score: f32, scores: Vec<f32>, seconds: f32 },
+}
+
+pub enum SessionError {
+    NoFile(String),
+    ReadingFile,
+    InvalidRow(usize, String),
+    UnknownLetter(String),
+    IncompatibleRules(Rules),
+    InvalidPattern(String),
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::NoFile(path) => write!(f, "No file named {}", path),
+            SessionError::ReadingFile => write!(f, "Error while reading file!"),
+            SessionError::InvalidRow(line, err) => write!(f, "Error on line {}: {}", line, err),
+            SessionError::UnknownLetter(letter) => write!(f, "'{}' is not a letter of the alphabet", letter),
+            SessionError::IncompatibleRules(rules) if rules.reveal_first() => write!(f, "The descriptor of {} games must start with the revealed letter, like 'b7'", rules),
+            SessionError::IncompatibleRules(rules) => write!(f, "The descriptor of {} games can't reveal a letter, give only the length like '5'", rules),
+            SessionError::InvalidPattern(err) => write!(f, "Invalid descriptor: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn session(rules: crate::rules::Rules, search: &str) -> super::Session {
+        use crate::alphabet::Alphabet;
+
+        super::Session::new(search.parse().unwrap(), rules, Alphabet::default()).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn result(result: &str) -> crate::mask::ResultState {
+        result.try_into().unwrap_or_else(|err: crate::mask::ConvertError| panic!("{}", err))
+    }
+
+    fn dico(words: &[&str]) -> Vec<Vec<u8>> {
+        words.iter().map(|word| crate::alphabet::Alphabet::default().encode(word).unwrap()).collect()
+    }
+
+    #[test]
+    fn save_and_load() {
+        use super::Session;
+        use crate::alphabet::Alphabet;
+        use crate::rules::Rules;
+
+        let path = std::env::temp_dir().join(format!("motus-session-{}", std::process::id()));
+        let games = [
+            (Rules::MOTUS, "b5", ["barres", "balles", "bulles"], "rrbbrr"),
+            (Rules::WORDLE, "5", ["carte", "tarte", "porte"], "brrrr"),
+            // the rules of motus aren't saved, the pattern has spaces
+            (Rules::MOTUS, "b..l.. +e", ["balles", "bulles", "barres"], "rbrrrr"),
+            (Rules::WORDLE, ".a... -z", ["carte", "tarte", "porte"], "brrrr"),
+        ];
+
+        for (rules, search, words, played) in games {
+            let dico = dico(&words);
+            let mut session = session(rules, search);
+            session.play(words[0], result(played)).unwrap();
+            session.save(&path).unwrap();
+
+            let loaded = Session::load(&path, Alphabet::default()).unwrap_or_else(|err| panic!("{}", err));
+            assert!(loaded.rules() == rules);
+            assert_eq!(loaded.desc().to_string(), search);
+            assert_eq!(loaded.history(), session.history());
+            assert_eq!(loaded.candidates(&dico).unwrap(), session.candidates(&dico).unwrap());
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn undo_turn() {
+        use crate::rules::Rules;
+
+        let dico = dico(&["barres", "balles", "bulles"]);
+        let mut session = session(Rules::MOTUS, "b5");
+
+        session.play("barres", result("rrbbrr")).unwrap();
+        assert_eq!(session.candidates(&dico).unwrap(), [1]);
+
+        session.play("balles", result("rrrrrr")).unwrap();
+        assert!(session.complet());
+
+        let (word, last) = session.undo().unwrap();
+        assert_eq!((word.as_str(), last.to_string().as_str()), ("balles", "rrrrrr"));
+        assert!(!session.complet());
+        assert_eq!(session.candidates(&dico).unwrap(), [1]);
+
+        // the mask is back to the revealed letter only
+        session.undo().unwrap();
+        assert_eq!(session.candidates(&dico).unwrap(), [0, 1, 2]);
+        assert!(session.undo().is_none());
+    }
+
+    #[test]
+    fn contradictions() {
+        use crate::mask::MaskError;
+        use crate::rules::Rules;
+
+        let mut session = session(Rules::MOTUS, "b5");
+
+        let err = session.play("carres", result("rbbbbb")).unwrap_err();
+        assert!(matches!(err, MaskError::Revealed(ref letter) if letter == "c"), "{}", err);
+
+        // the 'a' of the first turn is blue, whatever its place now
+        session.play("barres", result("rbbbbb")).unwrap();
+        let err = session.play("bullea", result("rbbbbr")).unwrap_err();
+        assert!(matches!(err, MaskError::Contradiction(1, ref letter) if letter == "a"), "{}", err);
+    }
+
+    #[test]
+    fn counts_checks() {
+        use crate::mask::MaskError;
+
+        let mut session = session("mastermind+first".parse().unwrap(), "b5");
+
+        let err = session.play("carres", result("rrbbbb")).unwrap_err();
+        assert!(matches!(err, MaskError::Revealed(ref letter) if letter == "c"), "{}", err);
+
+        let err = session.play("balle", result("rrbbb")).unwrap_err();
+        assert!(matches!(err, MaskError::IncompatibleLen(_)), "{}", err);
+
+        session.play("barres", result("rrbbbb")).unwrap();
+        assert_eq!(session.history().len(), 1);
+    }
+}
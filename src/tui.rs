@@ -0,0 +1,374 @@
+use std::io::Write;
+use std::path::Path;
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+
+use crate::dico::Dico;
+use crate::mask::{self, ResultColor, ResultState};
+use crate::session::Session;
+
+/// count of suggestions shown
+const SUGGESTIONS: usize = 10;
+/// left margin of the screen and width of a cell of the grid
+const MARGIN: u16 = 2;
+const CELL: u16 = 3;
+/// first row of the grid
+const GRID: u16 = 2;
+/// column of the candidates, next to the suggestions
+const CANDIDATES: u16 = 34;
+
+const HELP: &str = "←/→ move  ↑/↓/click color  r/y/b set  tab word  enter play  u undo  q quit";
+
+/// play the game in a full screen, saving every turn in `save` if provided
+pub fn run(session: Session, dir: &Path, save: Option<&Path>) {
+    let dico = match session.desc().load(dir) {
+        Ok(dico) if dico.is_empty() => {
+            eprintln!("No world of len {} found in {}", session.desc().word_len(), session.desc().path(dir).display());
+            return;
+        }
+        Ok(dico) => dico,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    let mut tui = Tui {
+        session,
+        dico,
+        save,
+        ranking: Vec::new(),
+        candidates: Vec::new(),
+        selected: 0,
+        colors: Vec::new(),
+        cursor: 0,
+        message: String::new(),
+    };
+
+    let result = Screen::enter().and_then(|_screen| tui.run());
+
+    // the screen is restored, errors can be printed
+    if let Err(err) = result {
+        eprintln!("{}", err);
+    } else if tui.session.complet() {
+        println!("{}", tui.session.share(None));
+    }
+}
+
+/// raw mode and alternate screen, left when dropped
+struct Screen;
+
+impl Screen {
+    fn enter() -> std::io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(std::io::stdout(), terminal::EnterAlternateScreen, event::EnableMouseCapture, cursor::Hide)?;
+
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(std::io::stdout(), event::DisableMouseCapture, terminal::LeaveAlternateScreen, cursor::Show);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct Tui<'a> {
+    session: Session,
+    dico: Dico,
+    save: Option<&'a Path>,
+    /// best words to play with their score, from the best
+    ranking: Vec<(usize, f32)>,
+    /// ids of the words still matching
+    candidates: Vec<usize>,
+    /// index in the ranking of the word to play
+    selected: usize,
+    /// colors given to the word to play
+    colors: Vec<ResultColor>,
+    /// cell being colored
+    cursor: usize,
+    message: String,
+}
+
+impl Tui<'_> {
+    fn run(&mut self) -> std::io::Result<()> {
+        let mut stdout = std::io::stdout();
+        self.refresh(&mut stdout)?;
+
+        loop {
+            self.draw(&mut stdout)?;
+
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                    KeyCode::Left | KeyCode::Char('h') => self.cursor = self.cursor.saturating_sub(1),
+                    KeyCode::Right | KeyCode::Char('l') => self.move_right(),
+                    KeyCode::Up | KeyCode::Char(' ') => self.cycle(self.cursor),
+                    KeyCode::Down => {
+                        // twice forward is once backward with three colors
+                        self.cycle(self.cursor);
+                        self.cycle(self.cursor);
+                    }
+                    KeyCode::Char(c @ ('r' | 'y' | 'b')) => {
+                        if let (Some(color), Ok(new)) = (self.colors.get_mut(self.cursor), ResultColor::try_from(c)) {
+                            *color = new;
+                        }
+
+                        self.move_right();
+                    }
+                    KeyCode::Tab => self.select((self.selected + 1) % self.ranking.len().max(1)),
+                    KeyCode::BackTab => self.select(self.selected.checked_sub(1).unwrap_or(self.ranking.len().saturating_sub(1))),
+                    KeyCode::Enter => self.play(&mut stdout)?,
+                    KeyCode::Char('u') | KeyCode::Backspace => match self.session.undo() {
+                        Some((word, result)) => {
+                            self.message = format!("Canceled: {} {}", word, result);
+                            self.save();
+                            self.refresh(&mut stdout)?;
+                        }
+                        None => self.message = "Nothing to undo".to_string(),
+                    },
+                    _ => (),
+                },
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                    self.click(mouse.column, mouse.row);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// id of the word to play
+    fn word(&self) -> Option<usize> {
+        self.ranking.get(self.selected).map(|&(id, _)| id)
+    }
+
+    fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.colors.len().saturating_sub(1));
+    }
+
+    fn cycle(&mut self, cell: usize) {
+        if let Some(color) = self.colors.get_mut(cell) {
+            *color = match color {
+                ResultColor::Blue => ResultColor::Yellow,
+                ResultColor::Yellow => ResultColor::Red,
+                ResultColor::Red => ResultColor::Blue,
+            };
+        }
+    }
+
+    fn select(&mut self, selected: usize) {
+        self.selected = selected;
+        self.reset_colors();
+    }
+
+    /// letters already known are red, the others blue
+    fn reset_colors(&mut self) {
+        let alphabet = self.session.alphabet();
+        let known = self.session.known();
+
+        self.cursor = 0;
+        self.colors = match self.word() {
+            Some(id) => self.dico.codes[id].iter()
+                .zip(known)
+                .map(|(&letter, known)| if known == Some(alphabet.letter(letter)) {
+                    ResultColor::Red
+                } else {
+                    ResultColor::Blue
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+    }
+
+    /// a click on a cell of the word to play cycles its color, a click on a suggestion selects it
+    fn click(&mut self, column: u16, row: u16) {
+        let played = self.session.history().len() as u16;
+        let suggestions = GRID + played + 3;
+
+        if row == GRID + played && column >= MARGIN {
+            let cell = ((column - MARGIN) / CELL) as usize;
+
+            if cell < self.colors.len() {
+                self.cursor = cell;
+                self.cycle(cell);
+            }
+        } else if row >= suggestions && column < CANDIDATES && ((row - suggestions) as usize) < self.ranking.len() {
+            self.select((row - suggestions) as usize);
+        }
+    }
+
+    fn play(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        let id = match self.word() {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+
+        match self.session.play(&self.dico.words[id], ResultState::from(self.colors.clone())) {
+            Ok(()) => {
+                self.message.clear();
+                self.save();
+                self.refresh(out)
+            }
+            Err(err) => {
+                self.message = err.to_string();
+                Ok(())
+            }
+        }
+    }
+
+    fn save(&mut self) {
+        if let Some(path) = self.save {
+            if let Err(err) = self.session.save(path) {
+                self.message = format!("Unable to save the game in {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// update the candidates and the suggestions after a turn
+    fn refresh(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        self.ranking.clear();
+        self.candidates = match self.session.candidates(&self.dico.codes) {
+            Ok(candidates) => candidates,
+            Err(err) => {
+                self.message = err.to_string();
+                Vec::new()
+            }
+        };
+
+        if self.session.complet() {
+            self.message = format!("Found in {} turns!", self.session.history().len());
+        } else if self.candidates.is_empty() {
+            self.message = "No word matches: the answer is not in the dictionary, undo the last result".to_string();
+        } else {
+            // scores may take some seconds
+            let message = std::mem::replace(&mut self.message, "Searching the best words...".to_string());
+            self.draw(out)?;
+            self.message = message;
+
+            match self.session.scores(&self.dico.codes) {
+                Ok(scores) => self.ranking = mask::rank(&scores)
+                    .into_iter()
+                    .filter(|&id| !scores[id].is_nan())
+                    .take(SUGGESTIONS)
+                    .map(|id| (id, scores[id]))
+                    .collect(),
+                Err(err) => self.message = err.to_string(),
+            }
+        }
+
+        self.select(0);
+        Ok(())
+    }
+
+    fn draw(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let (_, height) = terminal::size()?;
+        let alphabet = self.session.alphabet();
+
+        queue!(
+            out,
+            terminal::Clear(ClearType::All),
+            cursor::MoveTo(MARGIN, 0),
+            SetAttribute(Attribute::Bold),
+            Print(format!("{} {}", self.session.rules().to_string().to_uppercase(), self.session.desc())),
+            SetAttribute(Attribute::Reset),
+            Print(format!("  {} words remaining", self.candidates.len())),
+        )?;
+
+        let mut row = GRID;
+
+        for (word, result) in self.session.history() {
+            let letters = alphabet.encode(word).unwrap_or_default();
+
+            for (i, (&letter, &color)) in letters.iter().zip(result.colors()).enumerate() {
+                draw_cell(out, i, row, alphabet.letter(letter), color, false)?;
+            }
+
+            row += 1;
+        }
+
+        if let Some(id) = self.word() {
+            for (i, (&letter, &color)) in self.dico.codes[id].iter().zip(&self.colors).enumerate() {
+                draw_cell(out, i, row, alphabet.letter(letter), color, i == self.cursor)?;
+            }
+        }
+
+        row += 2;
+        queue!(
+            out,
+            cursor::MoveTo(MARGIN, row),
+            SetAttribute(Attribute::Underlined),
+            Print("Suggestions"),
+            cursor::MoveTo(CANDIDATES, row),
+            Print("Candidates"),
+            SetAttribute(Attribute::Reset),
+        )?;
+        row += 1;
+
+        for (i, &(id, score)) in self.ranking.iter().enumerate() {
+            let marker = if i == self.selected { '>' } else { ' ' };
+            queue!(
+                out,
+                cursor::MoveTo(MARGIN, row + i as u16),
+                Print(format!("{} {:>2}. {} ({:.2})", marker, i + 1, self.dico.words[id], score)),
+            )?;
+        }
+
+        // the message and the help are on the two last rows
+        let room = height.saturating_sub(row + 3) as usize;
+
+        for (i, &id) in self.candidates.iter().take(room).enumerate() {
+            let text = if i + 1 == room && self.candidates.len() > room {
+                format!("... {} more", self.candidates.len() - i)
+            } else {
+                self.dico.words[id].clone()
+            };
+
+            queue!(out, cursor::MoveTo(CANDIDATES, row + i as u16), Print(text))?;
+        }
+
+        queue!(
+            out,
+            cursor::MoveTo(MARGIN, height.saturating_sub(2)),
+            Print(&self.message),
+            cursor::MoveTo(MARGIN, height.saturating_sub(1)),
+            SetForegroundColor(Color::DarkGrey),
+            Print(HELP),
+            ResetColor,
+        )?;
+
+        out.flush()
+    }
+}
+
+/// letter in bold white on the background of the color, underlined under the cursor
+fn draw_cell(out: &mut impl Write, index: usize, row: u16, letter: &str, color: ResultColor, cursor: bool) -> std::io::Result<()> {
+    let background = match color {
+        ResultColor::Red => Color::DarkRed,
+        ResultColor::Yellow => Color::DarkYellow,
+        ResultColor::Blue => Color::DarkBlue,
+    };
+
+    queue!(
+        out,
+        cursor::MoveTo(MARGIN + index as u16 * CELL, row),
+        SetBackgroundColor(background),
+        SetForegroundColor(Color::White),
+        SetAttribute(Attribute::Bold),
+    )?;
+
+    if cursor {
+        queue!(out, SetAttribute(Attribute::Underlined))?;
+    }
+
+    queue!(
+        out,
+        Print(format!(" {} ", letter.to_uppercase())),
+        SetAttribute(Attribute::Reset),
+        ResetColor,
+    )
+}